@@ -0,0 +1,195 @@
+// --- Autenticación compartida: roles, claims JWT y extractores ---
+//
+// Cada servicio verifica los access tokens a su manera (identity-service con sus
+// propias claves, course-service con el JWKS publicado), así que el extractor
+// delega en `AuthState::verify_access_token`, implementado por el estado de cada servicio.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{request::Parts, StatusCode},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+
+/// Rol de un usuario. En la base de datos es el tipo `user_role`.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "postgres", derive(sqlx::Type))]
+#[cfg_attr(feature = "postgres", sqlx(type_name = "user_role", rename_all = "lowercase"))]
+pub enum Role {
+    Student,
+    Instructor,
+    Admin,
+}
+
+impl Role {
+    /// Nombre en minúsculas, como en la base de datos y en la configuración.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Student => "student",
+            Role::Instructor => "instructor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    /// Acepta el nombre en minúsculas (`student`, `instructor`, `admin`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "student" => Ok(Role::Student),
+            "instructor" => Ok(Role::Instructor),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("rol desconocido: {other}")),
+        }
+    }
+}
+
+/// Claims (contenido) de los access tokens JWT emitidos por identity-service.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: Uuid,  // Subject (el ID del usuario)
+    pub role: Role, // Rol del usuario
+    pub exp: i64,   // Expiration time
+    pub iat: i64,   // Issued at
+    pub jti: Uuid,  // ID único del token, usado para revocarlo
+    #[serde(default)]
+    pub email_verified: bool,
+    /// Momento de emisión en milisegundos. `iat` solo tiene segundos enteros y no
+    /// alcanza para saber si el token es anterior a una revocación del mismo segundo.
+    #[serde(default)]
+    pub iat_ms: Option<i64>,
+}
+
+impl Claims {
+    /// Momento de emisión en segundos con fracción, para compararlo con
+    /// `users.sessions_revoked_at`. Los tokens sin `iat_ms` se toman como emitidos al
+    /// principio de su segundo: ante la duda, una revocación de ese segundo los alcanza.
+    pub fn issued_at(&self) -> f64 {
+        self.iat_ms.unwrap_or(self.iat * 1000) as f64 / 1000.0
+    }
+}
+
+/// Estado de un servicio capaz de verificar access tokens.
+#[async_trait]
+pub trait AuthState: Send + Sync {
+    /// Verifica firma, expiración y revocación del token. Devuelve `401` si no es
+    /// válido, o `500` si no se pudo comprobar.
+    async fn verify_access_token(&self, token: &str) -> Result<Claims, StatusCode>;
+}
+
+/// Extrae el token de la cabecera `Authorization: Bearer <token>`.
+pub fn bearer_token(parts: &Parts) -> Option<&str> {
+    parts.headers
+        .get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+}
+
+impl<S: AuthState> FromRequestParts<S> for Claims {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts).ok_or(ApiError::Unauthorized)?;
+        Ok(state.verify_access_token(token).await?)
+    }
+}
+
+/// `Option<Claims>` para endpoints públicos que muestran más datos a usuarios
+/// autenticados: sin cabecera `Authorization` es `None`, pero un token inválido
+/// sigue respondiendo 401.
+impl<S: AuthState> OptionalFromRequestParts<S> for Claims {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        if !parts.headers.contains_key("authorization") {
+            return Ok(None);
+        }
+        <Claims as FromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+            .map(Some)
+    }
+}
+
+// --- Guards por rol ---
+
+/// Rol exigido por `RequireRole<R>`.
+pub trait RoleRequirement {
+    const ROLE: Role;
+}
+
+/// Marcadores de rol para usar con `RequireRole`, p. ej. `RequireRole<Instructor>`.
+pub mod roles {
+    use super::{Role, RoleRequirement};
+
+    pub struct Student;
+    pub struct Instructor;
+    pub struct Admin;
+
+    impl RoleRequirement for Student {
+        const ROLE: Role = Role::Student;
+    }
+
+    impl RoleRequirement for Instructor {
+        const ROLE: Role = Role::Instructor;
+    }
+
+    impl RoleRequirement for Admin {
+        const ROLE: Role = Role::Admin;
+    }
+}
+
+/// Claims de un access token válido cuyo rol es `R::ROLE`. Responde 403 a los
+/// demás usuarios. Da acceso a los claims por `Deref`.
+pub struct RequireRole<R: RoleRequirement> {
+    pub claims: Claims,
+    _role: PhantomData<R>,
+}
+
+impl<R: RoleRequirement> Deref for RequireRole<R> {
+    type Target = Claims;
+
+    fn deref(&self) -> &Claims {
+        &self.claims
+    }
+}
+
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    S: AuthState,
+    R: RoleRequirement,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = <Claims as FromRequestParts<S>>::from_request_parts(parts, state).await?;
+
+        if claims.role != R::ROLE {
+            return Err(ApiError::Forbidden(format!(
+                "Esta acción requiere el rol `{}`",
+                R::ROLE
+            )));
+        }
+
+        Ok(Self {
+            claims,
+            _role: PhantomData,
+        })
+    }
+}
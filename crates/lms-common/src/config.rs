@@ -0,0 +1,37 @@
+// --- Arranque y configuración comunes ---
+
+use std::env;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Carga `.env` (si existe) e inicializa el logging según `RUST_LOG` (por defecto `info`).
+/// Debe llamarse al comienzo de `main`, antes de leer la configuración.
+pub fn init(service_name: &str) {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
+            env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
+        ))
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+    tracing::info!("Iniciando {}", service_name);
+}
+
+/// Lee una variable de entorno obligatoria.
+pub fn required(key: &str) -> String {
+    env::var(key).unwrap_or_else(|_| panic!("{key} debe estar configurada"))
+}
+
+/// Lee una variable de entorno y la convierte a `T`, usando `default` si no está definida.
+pub fn env_or<T>(key: &str, default: T) -> T
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    env::var(key)
+        .ok()
+        .map(|v| v.parse().unwrap_or_else(|e| panic!("{key} tiene un valor inválido: {e:?}")))
+        .unwrap_or(default)
+}
@@ -0,0 +1,294 @@
+// --- Errores de la API (RFC 7807, `application/problem+json`) ---
+//
+// Los handlers devuelven `ApiError`, con un `detail` concreto siempre que haya
+// algo útil que decirle al cliente, y el middleware `problem_details` completa la
+// respuesta con `instance` y el ID de la petición. Como red de seguridad, las
+// respuestas de error sin cuerpo JSON (rechazos de los extractores de axum,
+// textos planos) también se convierten en problem+json, para que los clientes
+// siempre reciban el mismo formato.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{
+        header::{self, HeaderValue},
+        StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::validation::FieldError;
+
+/// Tipo de contenido de las respuestas de error.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Cabecera con el ID de la petición. Se respeta la que envíe el cliente o el proxy.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Tamaño máximo del cuerpo de error que se reescribe en el middleware.
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// Detalle de un error según RFC 7807. Se sirve como `application/problem+json`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI estable que identifica el tipo de error.
+    #[serde(rename = "type")]
+    #[schema(example = "urn:lms:problem:not-found")]
+    pub problem_type: String,
+    /// Resumen legible del tipo de error; no cambia entre ocurrencias.
+    #[schema(example = "Recurso no encontrado")]
+    pub title: String,
+    /// Código HTTP de la respuesta.
+    #[schema(example = 404)]
+    pub status: u16,
+    /// Explicación específica de esta ocurrencia.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "El curso no existe")]
+    pub detail: Option<String>,
+    /// Ruta de la petición que produjo el error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "/api/v1/courses/0b7c8a9e-3f7e-4c1a-9d55-2a4f1f0e8b21")]
+    pub instance: Option<String>,
+    /// ID de la petición (cabecera `X-Request-Id`), para cruzarlo con los logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Errores de validación por nombre de campo (solo en errores `validation`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, Vec<FieldError>>,
+}
+
+/// Error de un handler. Se convierte en una respuesta `application/problem+json`.
+#[derive(Debug)]
+pub enum ApiError {
+    /// 400: la petición no se puede procesar tal como está.
+    BadRequest(String),
+    /// 401: falta el token o no es válido.
+    Unauthorized,
+    /// 403: el usuario no tiene permiso para la acción.
+    Forbidden(String),
+    /// 404: el recurso no existe (o el usuario no puede verlo).
+    NotFound(String),
+    /// 409: la acción choca con el estado actual del recurso.
+    Conflict(String),
+    /// 422: el cuerpo no cumple las reglas de validación.
+    Validation {
+        detail: String,
+        errors: BTreeMap<String, Vec<FieldError>>,
+    },
+    /// 429: demasiados intentos; se indica cuándo reintentar.
+    TooManyRequests { retry_after: Duration },
+    /// 500: error inesperado. La causa se registra en el log, no se expone al cliente.
+    Internal,
+    /// Cualquier otro código, con un detalle opcional.
+    Status(StatusCode, Option<String>),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Status(status, _) => *status,
+        }
+    }
+
+    /// Construye el cuerpo del error (sin `instance` ni `request_id`, que añade el middleware).
+    pub fn to_problem(&self) -> ProblemDetails {
+        let status = self.status();
+        let (problem_type, title) = problem_type(status);
+        let detail = match self {
+            ApiError::BadRequest(detail)
+            | ApiError::Forbidden(detail)
+            | ApiError::NotFound(detail)
+            | ApiError::Conflict(detail)
+            | ApiError::Validation { detail, .. } => Some(detail.clone()),
+            ApiError::TooManyRequests { retry_after } => Some(format!(
+                "Reintenta dentro de {} s",
+                retry_after.as_secs().max(1)
+            )),
+            ApiError::Status(_, detail) => detail.clone(),
+            ApiError::Unauthorized | ApiError::Internal => None,
+        };
+        let errors = match self {
+            ApiError::Validation { errors, .. } => errors.clone(),
+            _ => BTreeMap::new(),
+        };
+
+        ProblemDetails {
+            problem_type,
+            title,
+            status: status.as_u16(),
+            detail,
+            instance: None,
+            request_id: None,
+            errors,
+        }
+    }
+}
+
+/// `type` y `title` de cada código. Los códigos sin tipo propio usan `about:blank`
+/// con la frase estándar del código, como indica la RFC 7807.
+fn problem_type(status: StatusCode) -> (String, String) {
+    let known = match status {
+        StatusCode::BAD_REQUEST => Some(("bad-request", "Petición inválida")),
+        StatusCode::UNAUTHORIZED => Some(("unauthorized", "No autenticado")),
+        StatusCode::FORBIDDEN => Some(("forbidden", "Acceso denegado")),
+        StatusCode::NOT_FOUND => Some(("not-found", "Recurso no encontrado")),
+        StatusCode::CONFLICT => Some(("conflict", "Conflicto con el estado actual del recurso")),
+        StatusCode::UNPROCESSABLE_ENTITY => Some(("validation", "Datos inválidos")),
+        StatusCode::TOO_MANY_REQUESTS => Some(("too-many-requests", "Demasiados intentos")),
+        StatusCode::INTERNAL_SERVER_ERROR => Some(("internal", "Error interno del servidor")),
+        _ => None,
+    };
+    match known {
+        Some((slug, title)) => (format!("urn:lms:problem:{slug}"), title.to_string()),
+        None => (
+            "about:blank".to_string(),
+            status.canonical_reason().unwrap_or("Error").to_string(),
+        ),
+    }
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        ApiError::Status(status, None)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let problem = self.to_problem();
+        let body = serde_json::to_vec(&problem).expect("ProblemDetails siempre es serializable");
+        let mut response = (self.status(), [(header::CONTENT_TYPE, PROBLEM_JSON)], body).into_response();
+        if let ApiError::TooManyRequests { retry_after } = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after.as_secs().max(1)));
+        }
+        // El middleware lo completa con `instance` y `request_id`
+        response.extensions_mut().insert(problem);
+        response
+    }
+}
+
+// --- Errores de la base de datos ---
+
+/// Traducción centralizada de los errores de sqlx: filas inexistentes y violaciones
+/// de restricciones se informan al cliente; el resto se registra y se responde 500.
+#[cfg(feature = "postgres")]
+impl From<sqlx::Error> for ApiError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::RowNotFound => ApiError::NotFound("El recurso no existe".into()),
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => ApiError::Conflict(
+                match db_err.constraint() {
+                    Some(constraint) => format!("Ya existe un registro con esos datos ({constraint})"),
+                    None => "Ya existe un registro con esos datos".into(),
+                },
+            ),
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => ApiError::Conflict(
+                match db_err.constraint() {
+                    Some(constraint) => format!("El registro referenciado no existe o sigue en uso ({constraint})"),
+                    None => "El registro referenciado no existe o sigue en uso".into(),
+                },
+            ),
+            sqlx::Error::Database(db_err) if db_err.is_check_violation() => ApiError::BadRequest(
+                match db_err.constraint() {
+                    Some(constraint) => format!("Los datos no cumplen la restricción {constraint}"),
+                    None => "Los datos no cumplen las restricciones".into(),
+                },
+            ),
+            _ => {
+                tracing::error!("Error de base de datos: {:?}", error);
+                ApiError::Internal
+            }
+        }
+    }
+}
+
+// --- Middleware ---
+
+/// ID de la petición actual, disponible como extensión de la petición.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Asigna un ID a cada petición (o respeta el de `X-Request-Id`), lo devuelve en la
+/// respuesta y convierte toda respuesta de error en `application/problem+json` con
+/// `instance` y `request_id`. Se registra con `axum::middleware::from_fn`.
+pub async fn problem_details(mut req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let instance = req.uri().path().to_string();
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let response = next.run(req).await;
+    let mut response = if response.status().is_client_error() || response.status().is_server_error() {
+        into_problem(response, instance, &request_id).await
+    } else {
+        response
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Completa el problem+json de un `ApiError`, o envuelve un error sin formato en uno
+/// nuevo usando el texto del cuerpo como `detail`. Los errores que ya traen JSON propio no se tocan.
+async fn into_problem(mut response: Response, instance: String, request_id: &str) -> Response {
+    if let Some(problem) = response.extensions_mut().remove::<ProblemDetails>() {
+        let (parts, _) = response.into_parts();
+        return with_body(parts, problem, instance, request_id);
+    }
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json") || v.starts_with(PROBLEM_JSON));
+    if is_json {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let detail = to_bytes(body, MAX_ERROR_BODY)
+        .await
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .filter(|text| !text.is_empty());
+    let problem = ApiError::Status(parts.status, detail).to_problem();
+    with_body(parts, problem, instance, request_id)
+}
+
+fn with_body(
+    mut parts: axum::http::response::Parts,
+    mut problem: ProblemDetails,
+    instance: String,
+    request_id: &str,
+) -> Response {
+    problem.instance = Some(instance);
+    problem.request_id = Some(request_id.to_string());
+    let body = serde_json::to_vec(&problem).expect("ProblemDetails siempre es serializable");
+    parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
@@ -0,0 +1,20 @@
+// --- Código compartido entre los servicios del LMS ---
+//
+// - `auth`: `Role`, `Claims`, el extractor Bearer y los guards por rol.
+// - `config`: arranque común (`.env`, logging) y lectura de variables de entorno.
+// - `error`: `ApiError` y el middleware que responde los errores como problem+json (RFC 7807).
+// - `mail` (feature `mail`): envío de correos por SMTP o al log.
+// - `tokens` (feature `tokens`): tokens opacos para enlaces y sesiones, y su hash.
+// - `validation`: extractor `ValidatedJson` y reglas de validación compartidas.
+
+pub mod auth;
+pub mod config;
+pub mod error;
+#[cfg(feature = "mail")]
+pub mod mail;
+#[cfg(feature = "tokens")]
+pub mod tokens;
+pub mod validation;
+
+pub use auth::{Claims, RequireRole, Role};
+pub use error::{ApiError, ProblemDetails};
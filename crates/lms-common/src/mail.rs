@@ -0,0 +1,169 @@
+// --- Envío de correos ---
+//
+// `MAIL_BACKEND` elige la implementación y es obligatoria:
+// - `smtp`: envío real vía `SMTP_HOST`/`SMTP_PORT` con credenciales opcionales.
+// - `log`: no envía nada. Solo para desarrollo y pruebas: registra destinatario y
+//   asunto en el log, el cuerpo solo a nivel `debug` y con los tokens de los enlaces
+//   tapados, y si está definida `MAIL_LOG_FILE` agrega el correo completo a ese archivo.
+// Los correos llevan enlaces con tokens que dan acceso a la cuenta (restablecer la
+// contraseña, verificar el email): no deben terminar en los logs por omisión.
+
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tokio::io::AsyncWriteExt;
+
+/// Un correo de texto plano listo para enviar.
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait MailSender: Send + Sync {
+    async fn send(&self, email: Email) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Envía el correo en segundo plano: la respuesta HTTP no espera al servidor de
+/// correo ni su tiempo de respuesta revela si la dirección existe.
+pub fn send_in_background(mailer: Arc<dyn MailSender>, email: Email) {
+    tokio::spawn(async move {
+        let to = email.to.clone();
+        if let Err(e) = mailer.send(email).await {
+            tracing::error!("Error al enviar correo a {}: {}", to, e);
+        }
+    });
+}
+
+/// Construye el backend configurado en las variables de entorno.
+pub fn from_env() -> Arc<dyn MailSender> {
+    let from = env::var("MAIL_FROM").unwrap_or_else(|_| "LMS <no-reply@lms.local>".into());
+
+    match env::var("MAIL_BACKEND").as_deref() {
+        Ok("smtp") => {
+            let host = env::var("SMTP_HOST").expect("SMTP_HOST debe estar configurado");
+            let port = env::var("SMTP_PORT")
+                .ok()
+                .map(|p| p.parse().expect("SMTP_PORT debe ser un número"));
+            let credentials = match (env::var("SMTP_USERNAME"), env::var("SMTP_PASSWORD")) {
+                (Ok(user), Ok(pass)) => Some(Credentials::new(user, pass)),
+                _ => None,
+            };
+            Arc::new(SmtpMailer::new(&host, port, credentials, &from))
+        }
+        Ok("log") => {
+            tracing::warn!("MAIL_BACKEND=log: los correos no se envían");
+            Arc::new(LogMailer {
+                file: env::var("MAIL_LOG_FILE").ok().map(PathBuf::from),
+            })
+        }
+        Ok(other) => panic!("MAIL_BACKEND desconocido: {other} (usar `smtp` o `log`)"),
+        Err(_) => panic!("MAIL_BACKEND debe estar configurado (`smtp`, o `log` solo para desarrollo)"),
+    }
+}
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    fn new(host: &str, port: Option<u16>, credentials: Option<Credentials>, from: &str) -> Self {
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .expect("SMTP_HOST inválido");
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+        if let Some(credentials) = credentials {
+            builder = builder.credentials(credentials);
+        }
+
+        Self {
+            transport: builder.build(),
+            from: from.parse().expect("MAIL_FROM debe ser una dirección válida"),
+        }
+    }
+}
+
+#[async_trait]
+impl MailSender for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email.to.parse()?)
+            .subject(email.subject)
+            .body(email.body)?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+pub struct LogMailer {
+    file: Option<PathBuf>,
+}
+
+#[async_trait]
+impl MailSender for LogMailer {
+    async fn send(&self, email: Email) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        tracing::info!(to = %email.to, subject = %email.subject, "Correo (no enviado)");
+        tracing::debug!(to = %email.to, "Cuerpo del correo:\n{}", redact_tokens(&email.body));
+
+        if let Some(path) = &self.file {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await?;
+            let entry = format!(
+                "To: {}\nSubject: {}\n\n{}\n---\n",
+                email.to, email.subject, email.body
+            );
+            file.write_all(entry.as_bytes()).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Tapa el valor de los parámetros `token=` de los enlaces.
+fn redact_tokens(body: &str) -> String {
+    const MARKER: &str = "token=";
+    let mut redacted = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find(MARKER) {
+        let (head, tail) = rest.split_at(start + MARKER.len());
+        redacted.push_str(head);
+        redacted.push_str("[redactado]");
+        let end = tail.find(|c: char| c.is_whitespace() || c == '&').unwrap_or(tail.len());
+        rest = &tail[end..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_every_token_in_links() {
+        let body = "Abre este enlace:\nhttp://lms/reset-password?token=abc123&x=1\nO este: http://lms/verify?token=def.ghi";
+        assert_eq!(
+            redact_tokens(body),
+            "Abre este enlace:\nhttp://lms/reset-password?token=[redactado]&x=1\nO este: http://lms/verify?token=[redactado]"
+        );
+    }
+
+    #[test]
+    fn leaves_bodies_without_tokens_untouched() {
+        let body = "El email de tu cuenta se cambió a nuevo@example.com.";
+        assert_eq!(redact_tokens(body), body);
+    }
+}
@@ -0,0 +1,19 @@
+// --- Tokens opacos ---
+//
+// Tokens aleatorios para enlaces y sesiones (refresh tokens, restablecimiento de
+// contraseña, invitaciones a cursos). Se entregan en claro una sola vez y en la
+// base de datos solo se guarda su hash: quien lea la tabla no puede usarlos.
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+/// Genera un token opaco (32 bytes aleatorios en hexadecimal).
+pub fn generate_token() -> String {
+    hex::encode(rand::thread_rng().r#gen::<[u8; 32]>())
+}
+
+/// Hash con el que se persiste un token opaco. No hace falta un KDF lento:
+/// el token tiene 256 bits de entropía.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,119 @@
+// --- Validación de payloads ---
+//
+// Los payloads declaran sus reglas con `#[derive(Validate)]` y los handlers los
+// reciben con `ValidatedJson<T>` en lugar de `Json<T>`. Si algún campo no cumple,
+// se responde 422 (`ApiError::Validation`) con los errores agrupados por campo,
+// para mostrarlos junto a cada campo del formulario.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    Json,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::error::ApiError;
+
+/// Error de una regla de validación sobre un campo.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Regla que falló (`length`, `email`, `password_strength`, ...).
+    #[schema(example = "length")]
+    code: String,
+    #[schema(example = "Debe tener entre 8 y 128 caracteres")]
+    message: String,
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        let errors = errors
+            .field_errors()
+            .into_iter()
+            .map(|(field, errors)| {
+                let errors = errors
+                    .iter()
+                    .map(|e| FieldError {
+                        code: e.code.to_string(),
+                        message: e
+                            .message
+                            .as_ref()
+                            .map_or_else(|| "Valor inválido".to_string(), Cow::to_string),
+                    })
+                    .collect();
+                (field.to_string(), errors)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        ApiError::Validation {
+            detail: "Los datos enviados no son válidos".into(),
+            errors,
+        }
+    }
+}
+
+/// Extractor que deserializa el cuerpo JSON y aplica sus reglas de validación.
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        // JSON mal formado o con campos faltantes / de otro tipo
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|rejection: JsonRejection| {
+                ApiError::Status(rejection.status(), Some(rejection.body_text()))
+            })?;
+
+        value.validate()?;
+
+        Ok(Self(value))
+    }
+}
+
+// --- Reglas personalizadas ---
+
+/// Rechaza textos vacíos o formados solo por espacios.
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError::new("blank").with_message("No puede estar vacío".into()));
+    }
+    Ok(())
+}
+
+/// Usernames: letras, números, `_`, `.` y `-` (sin `@`, para no confundirlos con un email al iniciar sesión).
+pub fn username_chars(value: &str) -> Result<(), ValidationError> {
+    if !value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')) {
+        return Err(ValidationError::new("username_chars")
+            .with_message("Solo puede contener letras, números, '_', '.' y '-'".into()));
+    }
+    Ok(())
+}
+
+/// Exige que la contraseña combine letras y números.
+pub fn password_strength(value: &str) -> Result<(), ValidationError> {
+    let has_letter = value.chars().any(char::is_alphabetic);
+    let has_digit = value.chars().any(|c| c.is_ascii_digit());
+    if !(has_letter && has_digit) {
+        return Err(ValidationError::new("password_strength")
+            .with_message("Debe contener al menos una letra y un número".into()));
+    }
+    Ok(())
+}
+
+/// Rechaza listas de IDs con elementos repetidos.
+pub fn unique_ids(ids: &[uuid::Uuid]) -> Result<(), ValidationError> {
+    let mut seen = std::collections::HashSet::with_capacity(ids.len());
+    if !ids.iter().all(|id| seen.insert(id)) {
+        return Err(ValidationError::new("unique").with_message("No puede contener IDs repetidos".into()));
+    }
+    Ok(())
+}
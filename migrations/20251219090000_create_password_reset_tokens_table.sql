@@ -0,0 +1,12 @@
+-- Crear la tabla de tokens de restablecimiento de contraseña
+-- Como con los refresh tokens, solo se guarda el hash SHA-256 del token enviado por correo.
+CREATE TABLE password_reset_tokens (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_password_reset_tokens_user_id ON password_reset_tokens(user_id);
@@ -0,0 +1,3 @@
+-- Los usernames son únicos sin distinguir mayúsculas: `Nurfog` y `nurfog` son
+-- el mismo usuario. El valor se guarda tal como se registró.
+CREATE UNIQUE INDEX users_username_lower_key ON users (LOWER(username));
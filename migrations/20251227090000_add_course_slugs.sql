@@ -0,0 +1,33 @@
+-- Valores por defecto para las columnas que el alta de cursos no envía
+ALTER TABLE courses
+    ALTER COLUMN course_order SET DEFAULT 0,
+    ALTER COLUMN course_status SET DEFAULT 'draft',
+    ALTER COLUMN course_visibility SET DEFAULT 'public';
+
+-- Normalizar los slugs existentes y desambiguar los repetidos con un sufijo numérico
+UPDATE courses c SET course_slug = r.slug
+FROM (
+    SELECT id,
+           CASE WHEN position = 1 THEN base ELSE base || '-' || position END AS slug
+    FROM (
+        SELECT id,
+               COALESCE(NULLIF(trim(both '-' from regexp_replace(lower(course_slug), '[^a-z0-9]+', '-', 'g')), ''), 'sin-titulo') AS base,
+               ROW_NUMBER() OVER (
+                   PARTITION BY COALESCE(NULLIF(trim(both '-' from regexp_replace(lower(course_slug), '[^a-z0-9]+', '-', 'g')), ''), 'sin-titulo')
+                   ORDER BY course_created_at, id
+               ) AS position
+        FROM courses
+    ) s
+) r
+WHERE c.id = r.id AND c.course_slug <> r.slug;
+
+CREATE UNIQUE INDEX courses_course_slug_key ON courses (course_slug);
+
+-- Slugs anteriores de cursos renombrados, para redirigir los enlaces viejos
+CREATE TABLE course_slug_redirects (
+    old_slug VARCHAR(255) PRIMARY KEY,
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_course_slug_redirects_course_id ON course_slug_redirects (course_id);
@@ -0,0 +1,7 @@
+-- Borrado lógico de cursos: quedan en la papelera hasta que se restauran o se purgan
+ALTER TABLE courses
+    ADD COLUMN course_deleted_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN course_deleted_by UUID;
+
+-- Papelera y purga de cursos borrados
+CREATE INDEX idx_courses_deleted_at ON courses (course_deleted_at) WHERE course_deleted_at IS NOT NULL;
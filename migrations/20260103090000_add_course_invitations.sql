@@ -0,0 +1,81 @@
+-- Cursos privados: no aparecen en el catálogo y solo se inscribe quien tiene un
+-- código de inscripción o una invitación del instructor.
+ALTER TYPE course_visibility ADD VALUE 'private';
+
+-- Códigos de inscripción, con usos y vencimiento opcionales
+CREATE TABLE course_enrollment_codes (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    code VARCHAR(32) NOT NULL UNIQUE,
+    max_uses INT CHECK (max_uses > 0),
+    uses INT NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_course_enrollment_codes_course ON course_enrollment_codes (course_id, created_at DESC);
+
+-- Invitaciones por email. Quedan pendientes hasta que el invitado las acepta con
+-- el enlace del correo o, si aún no tenía cuenta, hasta que se registra con ese email.
+-- Del token del enlace solo se guarda su hash.
+CREATE TABLE course_invitations (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    invited_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    accepted_by UUID,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Una sola invitación pendiente por curso y email (reinvitar la renueva)
+CREATE UNIQUE INDEX uq_course_invitations_pending
+    ON course_invitations (course_id, email)
+    WHERE accepted_at IS NULL AND revoked_at IS NULL;
+
+-- Invitaciones pendientes de un email, para reclamarlas al registrarse
+CREATE INDEX idx_course_invitations_email ON course_invitations (email) WHERE accepted_at IS NULL;
+
+-- Inscribe a un usuario recién registrado en los cursos publicados a los que lo
+-- invitaron con ese email. La llama identity-service al crear la cuenta. Aplica
+-- la misma regla de cupo que course-service: con el curso lleno, a la lista de espera.
+CREATE FUNCTION claim_course_invitations(p_user_id UUID, p_email TEXT) RETURNS INT
+LANGUAGE plpgsql AS $$
+DECLARE
+    inv RECORD;
+    v_max INT;
+    v_taken BIGINT;
+    v_full BOOLEAN;
+    v_claimed INT := 0;
+BEGIN
+    FOR inv IN
+        SELECT i.id, i.course_id
+        FROM course_invitations i JOIN courses c ON c.id = i.course_id
+        WHERE i.email = lower(p_email)
+          AND i.accepted_at IS NULL AND i.revoked_at IS NULL AND i.expires_at > NOW()
+          AND c.course_status = 'published' AND c.course_deleted_at IS NULL
+        ORDER BY i.created_at
+    LOOP
+        SELECT max_enrollments INTO v_max FROM courses WHERE id = inv.course_id FOR UPDATE;
+        SELECT COUNT(*) INTO v_taken FROM enrollments
+        WHERE course_id = inv.course_id AND enrollment_status IN ('active', 'completed');
+        v_full := v_max IS NOT NULL AND v_taken >= v_max;
+
+        INSERT INTO enrollments (course_id, student_id, enrollment_status, waitlisted_at)
+        VALUES (inv.course_id, p_user_id,
+                (CASE WHEN v_full THEN 'waitlisted' ELSE 'active' END)::enrollment_status,
+                CASE WHEN v_full THEN NOW() END)
+        ON CONFLICT (course_id, student_id) DO NOTHING;
+
+        UPDATE course_invitations SET accepted_at = NOW(), accepted_by = p_user_id WHERE id = inv.id;
+        v_claimed := v_claimed + 1;
+    END LOOP;
+
+    RETURN v_claimed;
+END;
+$$;
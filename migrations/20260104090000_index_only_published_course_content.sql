@@ -0,0 +1,34 @@
+-- La búsqueda solo indexa módulos y lecciones que los estudiantes pueden ver:
+-- los títulos de borradores y elementos ocultos no deben encontrarse ni aparecer
+-- en el fragmento de resultados.
+CREATE OR REPLACE FUNCTION course_search_document(p_name TEXT, p_description TEXT, p_course_id UUID)
+RETURNS TSVECTOR
+LANGUAGE sql STABLE AS $$
+    SELECT setweight(search_vector(p_name), 'A')
+        || setweight(search_vector(
+               (SELECT string_agg(m.module_name, ' ')
+                FROM modules m
+                WHERE m.course_id = p_course_id
+                  AND m.module_status = 'published' AND m.module_visibility = 'visible')), 'B')
+        || setweight(search_vector(
+               (SELECT string_agg(l.lesson_name, ' ')
+                FROM lessons l JOIN modules m ON m.id = l.module_id
+                WHERE m.course_id = p_course_id
+                  AND m.module_status = 'published' AND m.module_visibility = 'visible'
+                  AND l.lesson_status = 'published' AND l.lesson_visibility = 'visible')), 'B')
+        || setweight(search_vector(p_description), 'C')
+$$;
+
+-- Publicar, despublicar, ocultar o mostrar un módulo o lección también cambia el documento
+DROP TRIGGER modules_search_update ON modules;
+CREATE TRIGGER modules_search_update
+    AFTER INSERT OR UPDATE OF module_name, course_id, module_status, module_visibility OR DELETE ON modules
+    FOR EACH ROW EXECUTE FUNCTION modules_search_update();
+
+DROP TRIGGER lessons_search_update ON lessons;
+CREATE TRIGGER lessons_search_update
+    AFTER INSERT OR UPDATE OF lesson_name, module_id, lesson_status, lesson_visibility OR DELETE ON lessons
+    FOR EACH ROW EXECUTE FUNCTION lessons_search_update();
+
+-- Reindexar los cursos existentes sin el contenido no publicado
+UPDATE courses SET course_search = course_search_document(course_name, course_description, id);
@@ -0,0 +1,10 @@
+-- Desafío de segundo factor pendiente de cada usuario (el `mfa_token` que devuelve
+-- el login, identificado por su `jti`). Solo vale el último emitido, se canjea una
+-- sola vez y se invalida tras varios códigos incorrectos.
+CREATE TABLE mfa_login_challenges (
+    user_id UUID PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    jti UUID NOT NULL,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE
+);
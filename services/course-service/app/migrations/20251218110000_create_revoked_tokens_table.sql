@@ -0,0 +1,15 @@
+-- Lista de revocación de access tokens (JWT) consultada por los servicios
+-- Se identifica cada token por su claim `jti`; `expires_at` permite purgar
+-- las filas una vez que el token habría expirado de todas formas.
+CREATE TABLE revoked_tokens (
+    jti UUID PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_revoked_tokens_expires_at ON revoked_tokens(expires_at);
+
+-- Todo token emitido (`iat`) hasta este instante queda revocado.
+-- Lo usa el endpoint de administración que cierra todas las sesiones de un usuario.
+ALTER TABLE users ADD COLUMN sessions_revoked_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,28 @@
+-- Estado y visibilidad de las lecciones con los mismos tipos que los módulos
+ALTER TABLE lessons
+    ALTER COLUMN lesson_status TYPE publish_status
+        USING (CASE WHEN lesson_status = 'published' THEN 'published' ELSE 'draft' END)::publish_status,
+    ALTER COLUMN lesson_status SET DEFAULT 'draft',
+    ALTER COLUMN lesson_visibility TYPE item_visibility
+        USING (CASE WHEN lesson_visibility = 'hidden' THEN 'hidden' ELSE 'visible' END)::item_visibility,
+    ALTER COLUMN lesson_visibility SET DEFAULT 'visible';
+
+-- Contenido tipado de la lección (markdown, video, recurso embebido o quiz), el HTML
+-- ya saneado que se entrega a los estudiantes y la versión vigente del contenido
+ALTER TABLE lessons
+    ADD COLUMN lesson_content JSONB,
+    ADD COLUMN lesson_content_html TEXT,
+    ADD COLUMN lesson_content_version INT NOT NULL DEFAULT 0;
+
+-- Historial de versiones del contenido; incluye la versión vigente
+CREATE TABLE lesson_content_versions (
+    lesson_id UUID NOT NULL REFERENCES lessons(id) ON DELETE CASCADE,
+    version INT NOT NULL,
+    content JSONB NOT NULL,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (lesson_id, version)
+);
+
+-- Listado de las lecciones de un módulo en orden
+CREATE INDEX idx_lessons_module_id_order ON lessons(module_id, lesson_order);
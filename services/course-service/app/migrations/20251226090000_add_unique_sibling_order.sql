@@ -0,0 +1,27 @@
+-- Renumerar los órdenes actuales como 1..n dentro de cada curso / módulo
+UPDATE modules m SET module_order = r.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY course_id ORDER BY module_order, module_created_at, id) AS position
+    FROM modules
+) r
+WHERE m.id = r.id;
+
+UPDATE lessons l SET lesson_order = r.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY module_id ORDER BY lesson_order, lesson_created_at, id) AS position
+    FROM lessons
+) r
+WHERE l.id = r.id;
+
+-- Dos hermanos no pueden compartir posición. Las restricciones se comprueban al
+-- confirmar la transacción, para poder reordenar con actualizaciones intermedias.
+DROP INDEX idx_modules_course_id_order;
+DROP INDEX idx_lessons_module_id_order;
+
+ALTER TABLE modules
+    ADD CONSTRAINT modules_course_id_module_order_key
+    UNIQUE (course_id, module_order) DEFERRABLE INITIALLY DEFERRED;
+
+ALTER TABLE lessons
+    ADD CONSTRAINT lessons_module_id_lesson_order_key
+    UNIQUE (module_id, lesson_order) DEFERRABLE INITIALLY DEFERRED;
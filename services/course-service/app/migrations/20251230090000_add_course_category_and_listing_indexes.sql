@@ -0,0 +1,9 @@
+-- Categoría del curso (texto libre; el filtro no distingue mayúsculas)
+ALTER TABLE courses ADD COLUMN course_category VARCHAR(100);
+
+CREATE INDEX idx_courses_category ON courses (lower(course_category)) WHERE course_deleted_at IS NULL;
+
+-- Paginación por cursor del listado de cursos en cada orden disponible
+CREATE INDEX idx_courses_created_at_id ON courses (course_created_at, id) WHERE course_deleted_at IS NULL;
+CREATE INDEX idx_courses_name_id ON courses (lower(course_name), id) WHERE course_deleted_at IS NULL;
+CREATE INDEX idx_courses_instructor_id ON courses (instructor_id);
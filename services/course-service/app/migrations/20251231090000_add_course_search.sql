@@ -0,0 +1,96 @@
+-- Búsqueda de texto completo en el catálogo, en español y sin distinguir tildes
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+CREATE TEXT SEARCH CONFIGURATION spanish_unaccent (COPY = spanish);
+ALTER TEXT SEARCH CONFIGURATION spanish_unaccent
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, spanish_stem;
+
+-- Cada texto se indexa dos veces: con `spanish` (las raíces de palabras con tilde,
+-- como "programación" → "program", solo se reconocen con la tilde) y con
+-- `spanish_unaccent` (para quien escribe sin tildes). La consulta prueba ambas.
+CREATE FUNCTION search_vector(p_text TEXT) RETURNS TSVECTOR
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT to_tsvector('spanish', coalesce(p_text, '')) || to_tsvector('spanish_unaccent', coalesce(p_text, ''))
+$$;
+
+CREATE FUNCTION search_query(p_query TEXT) RETURNS TSQUERY
+LANGUAGE sql STABLE AS $$
+    SELECT websearch_to_tsquery('spanish', p_query) || websearch_to_tsquery('spanish_unaccent', p_query)
+$$;
+
+ALTER TABLE courses ADD COLUMN course_search TSVECTOR NOT NULL DEFAULT ''::tsvector;
+
+-- Documento de búsqueda del curso: el nombre pesa más (A) que los títulos de
+-- módulos y lecciones (B), y estos más que la descripción (C).
+CREATE FUNCTION course_search_document(p_name TEXT, p_description TEXT, p_course_id UUID)
+RETURNS TSVECTOR
+LANGUAGE sql STABLE AS $$
+    SELECT setweight(search_vector(p_name), 'A')
+        || setweight(search_vector(
+               (SELECT string_agg(m.module_name, ' ') FROM modules m WHERE m.course_id = p_course_id)), 'B')
+        || setweight(search_vector(
+               (SELECT string_agg(l.lesson_name, ' ')
+                FROM lessons l JOIN modules m ON m.id = l.module_id
+                WHERE m.course_id = p_course_id)), 'B')
+        || setweight(search_vector(p_description), 'C')
+$$;
+
+-- Al crear un curso o cambiar su nombre o descripción
+CREATE FUNCTION courses_search_update() RETURNS TRIGGER
+LANGUAGE plpgsql AS $$
+BEGIN
+    NEW.course_search := course_search_document(NEW.course_name, NEW.course_description, NEW.id);
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER courses_search_update
+    BEFORE INSERT OR UPDATE OF course_name, course_description ON courses
+    FOR EACH ROW EXECUTE FUNCTION courses_search_update();
+
+-- Al crear, renombrar, mover o borrar módulos y lecciones se recalcula el curso afectado
+CREATE FUNCTION refresh_course_search(p_course_id UUID) RETURNS VOID
+LANGUAGE sql AS $$
+    UPDATE courses
+    SET course_search = course_search_document(course_name, course_description, id)
+    WHERE id = p_course_id
+$$;
+
+CREATE FUNCTION modules_search_update() RETURNS TRIGGER
+LANGUAGE plpgsql AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM refresh_course_search(OLD.course_id);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND (TG_OP = 'INSERT' OR NEW.course_id IS DISTINCT FROM OLD.course_id) THEN
+        PERFORM refresh_course_search(NEW.course_id);
+    END IF;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER modules_search_update
+    AFTER INSERT OR UPDATE OF module_name, course_id OR DELETE ON modules
+    FOR EACH ROW EXECUTE FUNCTION modules_search_update();
+
+CREATE FUNCTION lessons_search_update() RETURNS TRIGGER
+LANGUAGE plpgsql AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM refresh_course_search((SELECT course_id FROM modules WHERE id = OLD.module_id));
+    END IF;
+    IF TG_OP = 'INSERT' OR (TG_OP = 'UPDATE' AND NEW.module_id IS DISTINCT FROM OLD.module_id) THEN
+        PERFORM refresh_course_search((SELECT course_id FROM modules WHERE id = NEW.module_id));
+    END IF;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER lessons_search_update
+    AFTER INSERT OR UPDATE OF lesson_name, module_id OR DELETE ON lessons
+    FOR EACH ROW EXECUTE FUNCTION lessons_search_update();
+
+-- Indexar los cursos existentes
+UPDATE courses SET course_search = course_search_document(course_name, course_description, id);
+
+CREATE INDEX idx_courses_search ON courses USING GIN (course_search);
@@ -0,0 +1,12 @@
+-- La consulta se arma solo con `spanish_unaccent`. Con el OR de las dos
+-- configuraciones, una exclusión (`-palabra`) bastaba con que fallara en una rama:
+-- "python -programación" encontraba "programacion" porque la rama `spanish` busca
+-- la raíz "program". Sin tildes, la palabra de la consulta y la del documento dan
+-- el mismo término se escriban como se escriban, así que las exclusiones valen para
+-- ambas formas. El documento sigue indexado con las dos configuraciones: la parte
+-- `spanish` aporta las raíces de las palabras con tilde ("programa" encuentra
+-- "programación").
+CREATE OR REPLACE FUNCTION search_query(p_query TEXT) RETURNS TSQUERY
+LANGUAGE sql STABLE AS $$
+    SELECT websearch_to_tsquery('spanish_unaccent', p_query)
+$$;
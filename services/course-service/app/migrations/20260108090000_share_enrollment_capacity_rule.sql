@@ -0,0 +1,78 @@
+-- Una sola implementación de la regla de cupo: la usan course-service (inscripción,
+-- cambios de estado y promoción de la lista de espera) y `claim_course_invitations`.
+
+-- Plazas libres del curso (activos y completados ocupan plaza); NULL si no hay límite
+CREATE FUNCTION course_free_seats(p_course_id UUID) RETURNS BIGINT
+LANGUAGE sql STABLE AS $$
+    SELECT CASE WHEN c.max_enrollments IS NULL THEN NULL
+                ELSE GREATEST(c.max_enrollments - (
+                    SELECT COUNT(*) FROM enrollments e
+                    WHERE e.course_id = c.id AND e.enrollment_status IN ('active', 'completed')), 0)
+           END
+    FROM courses c WHERE c.id = p_course_id
+$$;
+
+-- Inscribe al estudiante, o lo pone en la lista de espera si el curso está lleno,
+-- con el curso bloqueado hasta el final de la transacción. Una baja anterior se
+-- reactiva; si ya estaba inscrito, en espera o completó el curso no devuelve filas.
+-- Las reglas de acceso (estado, visibilidad, periodo de inscripción) las aplica quien llama.
+CREATE FUNCTION enroll_in_course(p_course_id UUID, p_student_id UUID) RETURNS SETOF enrollments
+LANGUAGE plpgsql AS $$
+DECLARE
+    v_free BIGINT;
+    v_status enrollment_status;
+BEGIN
+    PERFORM 1 FROM courses WHERE id = p_course_id FOR UPDATE;
+    v_free := course_free_seats(p_course_id);
+    v_status := CASE WHEN v_free IS NULL OR v_free > 0 THEN 'active' ELSE 'waitlisted' END;
+
+    RETURN QUERY
+    INSERT INTO enrollments AS e (course_id, student_id, enrollment_status, waitlisted_at)
+    VALUES (p_course_id, p_student_id, v_status, CASE WHEN v_status = 'waitlisted' THEN NOW() END)
+    ON CONFLICT (course_id, student_id) DO UPDATE SET
+        enrollment_status = EXCLUDED.enrollment_status,
+        enrolled_at = NOW(),
+        completed_at = NULL,
+        dropped_at = NULL,
+        waitlisted_at = EXCLUDED.waitlisted_at,
+        enrollment_updated_at = NOW()
+    WHERE e.enrollment_status = 'dropped'
+    RETURNING e.*;
+END;
+$$;
+
+-- Las invitaciones se reclaman con `enroll_in_course`, que también reactiva una baja
+-- anterior. Una invitación solo se marca aceptada si la inscripción se creó o se
+-- reactivó. Como al aceptarla con el enlace, no depende del periodo de inscripción.
+CREATE OR REPLACE FUNCTION claim_course_invitations(p_user_id UUID, p_email TEXT) RETURNS INT
+LANGUAGE plpgsql AS $$
+DECLARE
+    inv RECORD;
+    v_claimed INT := 0;
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM users
+        WHERE id = p_user_id AND email = lower(p_email)
+          AND role = 'student' AND email_verified_at IS NOT NULL AND deactivated_at IS NULL
+    ) THEN
+        RETURN 0;
+    END IF;
+
+    FOR inv IN
+        SELECT i.id, i.course_id
+        FROM course_invitations i JOIN courses c ON c.id = i.course_id
+        WHERE i.email = lower(p_email)
+          AND i.accepted_at IS NULL AND i.revoked_at IS NULL AND i.expires_at > NOW()
+          AND c.course_status = 'published' AND c.course_deleted_at IS NULL
+        ORDER BY i.created_at
+    LOOP
+        PERFORM 1 FROM enroll_in_course(inv.course_id, p_user_id);
+        IF FOUND THEN
+            UPDATE course_invitations SET accepted_at = NOW(), accepted_by = p_user_id WHERE id = inv.id;
+            v_claimed := v_claimed + 1;
+        END IF;
+    END LOOP;
+
+    RETURN v_claimed;
+END;
+$$;
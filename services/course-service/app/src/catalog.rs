@@ -0,0 +1,248 @@
+// --- Listado de cursos ---
+//
+// `GET /api/v1/courses` pagina por cursor: cada página trae `next_cursor`, que se
+// envía como `after` para pedir la siguiente. El cursor codifica el orden y la
+// posición del último curso, así las páginas no se solapan ni se saltan cursos
+// aunque se creen otros mientras se recorre el listado.
+
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use lms_common::{ApiError, Claims, ProblemDetails, Role};
+
+use crate::lifecycle::{CourseStatus, CourseVisibility};
+use crate::{AppState, Course};
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+
+/// Orden del listado de cursos.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CourseSort {
+    /// Más recientes primero.
+    #[default]
+    Newest,
+    /// Más antiguos primero.
+    Oldest,
+    /// Por nombre, de la A a la Z (sin distinguir mayúsculas).
+    Name,
+    /// Por nombre, de la Z a la A (sin distinguir mayúsculas).
+    NameDesc,
+}
+
+impl CourseSort {
+    fn as_str(self) -> &'static str {
+        match self {
+            CourseSort::Newest => "newest",
+            CourseSort::Oldest => "oldest",
+            CourseSort::Name => "name",
+            CourseSort::NameDesc => "name_desc",
+        }
+    }
+
+    fn by_name(self) -> bool {
+        matches!(self, CourseSort::Name | CourseSort::NameDesc)
+    }
+}
+
+/// Filtros, orden y paginación del listado de cursos.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListCoursesQuery {
+    /// Cursos por página (máximo 100, por defecto 20).
+    limit: Option<i64>,
+    /// Cursor de la página anterior (`next_cursor`). Solo vale con el mismo `sort`.
+    after: Option<String>,
+    /// Filtrar por instructor.
+    instructor_id: Option<Uuid>,
+    /// Filtrar por estado. Quien no administra el curso solo ve los publicados.
+    status: Option<CourseStatus>,
+    /// Filtrar por visibilidad.
+    visibility: Option<CourseVisibility>,
+    /// Creados en esta fecha o después (RFC 3339).
+    created_from: Option<DateTime<Utc>>,
+    /// Creados en esta fecha o antes (RFC 3339).
+    created_to: Option<DateTime<Utc>>,
+    /// Filtrar por categoría (sin distinguir mayúsculas).
+    category: Option<String>,
+    /// Orden (por defecto `newest`).
+    sort: Option<CourseSort>,
+}
+
+/// Página del listado de cursos.
+#[derive(Serialize, ToSchema)]
+pub struct CoursePage {
+    items: Vec<Course>,
+    /// Cursor para pedir la página siguiente (`after`); `null` en la última página.
+    next_cursor: Option<String>,
+    /// Cursos que cumplen los filtros, sin contar el cursor. Es aproximado: puede
+    /// cambiar entre páginas si se crean o publican cursos mientras tanto.
+    total_estimate: i64,
+}
+
+/// Posición del último curso de una página.
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    sort: CourseSort,
+    /// Valor de la columna de orden: fecha de creación o nombre (la comparación
+    /// por nombre aplica `lower()` en la base de datos, igual que el orden).
+    key: String,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn after(course: &Course, sort: CourseSort) -> Option<Self> {
+        let key = if sort.by_name() {
+            course.course_name.clone()
+        } else {
+            course.course_created_at?.to_rfc3339()
+        };
+        Some(Cursor { sort, key, id: course.id })
+    }
+
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).expect("el cursor siempre es serializable"))
+    }
+
+    fn decode(value: &str, sort: CourseSort) -> Result<Self, ApiError> {
+        let invalid = || ApiError::BadRequest("El cursor `after` no es válido".into());
+        let bytes = URL_SAFE_NO_PAD.decode(value).map_err(|_| invalid())?;
+        let cursor: Cursor = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        if cursor.sort != sort {
+            return Err(ApiError::BadRequest(
+                "El cursor `after` corresponde a otro orden; empieza de nuevo sin cursor".into(),
+            ));
+        }
+        if !sort.by_name() && DateTime::parse_from_rfc3339(&cursor.key).is_err() {
+            return Err(invalid());
+        }
+        Ok(cursor)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses",
+    params(ListCoursesQuery),
+    responses(
+        (status = 200, description = "Página del catálogo: cursos publicados y públicos. Los instructores ven además todos sus cursos y los administradores, todos", body = CoursePage),
+        (status = 400, description = "Parámetros o cursor inválidos", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Token inválido", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        (),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_courses(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Query(query): Query<ListCoursesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let is_admin = claims.as_ref().is_some_and(|c| c.role == Role::Admin);
+    let user_id = claims.as_ref().map(|c| c.sub);
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let sort = query.sort.unwrap_or_default();
+    let category = query.category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    let cursor = query.after.as_deref().map(|after| Cursor::decode(after, sort)).transpose()?;
+    let after_id = cursor.as_ref().map(|c| c.id);
+    let after_created_at = cursor
+        .as_ref()
+        .filter(|_| !sort.by_name())
+        .and_then(|c| DateTime::parse_from_rfc3339(&c.key).ok())
+        .map(|t| t.with_timezone(&Utc));
+    let after_name = cursor.as_ref().filter(|_| sort.by_name()).map(|c| c.key.as_str());
+
+    let total_estimate = sqlx::query_scalar!(
+        "SELECT COUNT(*) AS \"count!\"
+         FROM courses
+         WHERE course_deleted_at IS NULL
+           AND ($1 OR instructor_id = $2 OR (course_status = 'published' AND course_visibility = 'public'))
+           AND ($3::uuid IS NULL OR instructor_id = $3)
+           AND ($4::course_status IS NULL OR course_status = $4)
+           AND ($5::course_visibility IS NULL OR course_visibility = $5)
+           AND ($6::timestamptz IS NULL OR course_created_at >= $6)
+           AND ($7::timestamptz IS NULL OR course_created_at <= $7)
+           AND ($8::text IS NULL OR lower(course_category) = lower($8))",
+        is_admin,
+        user_id,
+        query.instructor_id,
+        query.status as Option<CourseStatus>,
+        query.visibility as Option<CourseVisibility>,
+        query.created_from,
+        query.created_to,
+        category
+    )
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    // Se pide un curso de más para saber si hay página siguiente
+    let mut items = sqlx::query_as!(
+        Course,
+        "SELECT id, instructor_id, course_name, course_slug, course_description,
+                course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                course_published_at, course_created_at
+         FROM courses
+         WHERE course_deleted_at IS NULL
+           AND ($1 OR instructor_id = $2 OR (course_status = 'published' AND course_visibility = 'public'))
+           AND ($3::uuid IS NULL OR instructor_id = $3)
+           AND ($4::course_status IS NULL OR course_status = $4)
+           AND ($5::course_visibility IS NULL OR course_visibility = $5)
+           AND ($6::timestamptz IS NULL OR course_created_at >= $6)
+           AND ($7::timestamptz IS NULL OR course_created_at <= $7)
+           AND ($8::text IS NULL OR lower(course_category) = lower($8))
+           AND ($10::uuid IS NULL OR CASE $9
+                   WHEN 'oldest' THEN (course_created_at, id) > ($11::timestamptz, $10::uuid)
+                   WHEN 'name' THEN (lower(course_name), id) > (lower($12::text), $10::uuid)
+                   WHEN 'name_desc' THEN (lower(course_name), id) < (lower($12::text), $10::uuid)
+                   ELSE (course_created_at, id) < ($11::timestamptz, $10::uuid)
+               END)
+         ORDER BY
+             CASE WHEN $9 = 'oldest' THEN course_created_at END ASC,
+             CASE WHEN $9 = 'newest' THEN course_created_at END DESC,
+             CASE WHEN $9 = 'name' THEN lower(course_name) END ASC,
+             CASE WHEN $9 = 'name_desc' THEN lower(course_name) END DESC,
+             CASE WHEN $9 IN ('oldest', 'name') THEN id END ASC,
+             id DESC
+         LIMIT $13",
+        is_admin,
+        user_id,
+        query.instructor_id,
+        query.status as Option<CourseStatus>,
+        query.visibility as Option<CourseVisibility>,
+        query.created_from,
+        query.created_to,
+        category,
+        sort.as_str(),
+        after_id,
+        after_created_at,
+        after_name,
+        limit + 1
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    let next_cursor = if items.len() as i64 > limit {
+        items.truncate(limit as usize);
+        items.last().and_then(|last| Cursor::after(last, sort)).map(|c| c.encode())
+    } else {
+        None
+    };
+
+    Ok(Json(CoursePage {
+        items,
+        next_cursor,
+        total_estimate,
+    }))
+}
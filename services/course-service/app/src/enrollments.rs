@@ -0,0 +1,642 @@
+// --- Inscripciones ---
+//
+// Un estudiante se inscribe en un curso publicado con `POST /api/v1/courses/{id}/enrollment`
+// y se da de baja con `DELETE` en la misma ruta. Solo los inscritos (activos o que
+// ya lo completaron) ven el contenido de las lecciones; el temario sigue siendo público.
+// El instructor del curso y los administradores ven la lista de inscritos y pueden
+// cambiar el estado de cada inscripción.
+//
+// Cada curso puede limitar sus plazas (`max_enrollments`) y el periodo de inscripción.
+// Con el curso lleno, las inscripciones pasan a una lista de espera y, cuando se libera
+// una plaza, se promueve al primero de la lista. Todo cambio que ocupa o libera plazas
+// bloquea antes la fila del curso, así dos peticiones simultáneas no pueden quedarse
+// con la misma plaza.
+//
+// En los cursos privados no hay inscripción libre: se entra con un código o una
+// invitación del instructor (ver `invitations`).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use lms_common::auth::roles::Student;
+use lms_common::validation::ValidatedJson;
+use lms_common::{ApiError, Claims, ProblemDetails, RequireRole};
+
+use crate::lifecycle::{CourseStatus, CourseVisibility};
+use crate::{can_manage_course, course_instructor, AppState, Course};
+
+/// Estado de una inscripción. En la base de datos es el tipo `enrollment_status`.
+#[derive(Debug, Serialize, Deserialize, ToSchema, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "enrollment_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EnrollmentStatus {
+    /// Cursando.
+    Active,
+    /// Terminó el curso; conserva el acceso a las lecciones.
+    Completed,
+    /// Se dio de baja o lo dieron de baja; pierde el acceso.
+    Dropped,
+    /// Esperando plaza; pasa a `active` cuando se libera una, por orden de llegada.
+    Waitlisted,
+}
+
+/// Inscripción de un estudiante en un curso.
+#[derive(Serialize, ToSchema)]
+pub struct Enrollment {
+    course_id: Uuid,
+    student_id: Uuid,
+    pub enrollment_status: EnrollmentStatus,
+    /// Fecha de la última inscripción (se renueva al volver a inscribirse y al
+    /// salir de la lista de espera).
+    enrolled_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    dropped_at: Option<DateTime<Utc>>,
+    /// Desde cuándo espera plaza; fija el orden de la lista de espera.
+    waitlisted_at: Option<DateTime<Utc>>,
+}
+
+/// Curso en el que está inscrito el estudiante, con los datos de su inscripción.
+#[derive(Serialize, ToSchema)]
+pub struct EnrolledCourse {
+    #[serde(flatten)]
+    course: Course,
+    enrollment_status: EnrollmentStatus,
+    enrolled_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    dropped_at: Option<DateTime<Utc>>,
+    /// Puesto en la lista de espera, empezando en 1; `null` si no está esperando plaza.
+    waitlist_position: Option<i64>,
+}
+
+/// Filtro por estado de los listados de inscripciones.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EnrollmentsQuery {
+    /// Solo las inscripciones en este estado.
+    status: Option<EnrollmentStatus>,
+}
+
+/// Payload para cambiar el estado de una inscripción. La lista de espera la
+/// gestiona el servicio, así que no se acepta `waitlisted`.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct UpdateEnrollment {
+    enrollment_status: EnrollmentStatus,
+}
+
+/// Cupo y periodo de inscripción de un curso. Reemplaza la configuración anterior:
+/// un campo ausente o `null` quita el límite correspondiente.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct EnrollmentSettings {
+    #[schema(example = 30, minimum = 1)]
+    #[validate(range(min = 1, message = "Debe ser al menos 1"))]
+    max_enrollments: Option<i32>,
+    enrollment_opens_at: Option<DateTime<Utc>>,
+    enrollment_closes_at: Option<DateTime<Utc>>,
+}
+
+/// Por dónde llega una inscripción; decide qué restricciones del curso se aplican.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnrollmentSource {
+    /// El estudiante se inscribe por su cuenta. No vale para cursos privados.
+    SelfService,
+    /// Con un código de inscripción.
+    Code,
+    /// Invitado o importado por el instructor: no depende del periodo de inscripción.
+    Instructor,
+}
+
+/// Datos del curso que deciden si se admite una inscripción.
+struct LockedCourse {
+    instructor_id: Uuid,
+    course_status: CourseStatus,
+    course_visibility: CourseVisibility,
+    enrollment_opens_at: Option<DateTime<Utc>>,
+    enrollment_closes_at: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/enrollment",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 201, description = "Inscripción creada, o reactivada si el estudiante se había dado de baja. Si el curso está lleno queda en `waitlisted`", body = Enrollment),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es estudiante o no verificó su email, o el curso es privado)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado (o no publicado)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El estudiante ya está inscrito, en lista de espera o completó el curso; el curso está archivado; o la inscripción no está abierta", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn enroll(
+    State(state): State<AppState>,
+    student: RequireRole<Student>,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_verified_email(&state, &student)?;
+
+    let mut tx = state.db_pool.begin().await?;
+    let enrollment = enroll_student(&mut tx, course_id, student.sub, EnrollmentSource::SelfService)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Ya estás inscrito en este curso o en su lista de espera".into()))?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(enrollment)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/courses/{id}/enrollment",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 204, description = "Inscripción dada de baja (o salida de la lista de espera); el estudiante pierde el acceso a las lecciones y su plaza pasa al primero de la lista de espera"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es estudiante)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "El estudiante no está inscrito en el curso", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn unenroll(
+    State(state): State<AppState>,
+    student: RequireRole<Student>,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+    let course = lock_course(&mut tx, course_id).await?;
+
+    let result = sqlx::query!(
+        "UPDATE enrollments SET
+            enrollment_status = 'dropped',
+            dropped_at = NOW(),
+            enrollment_updated_at = NOW()
+         WHERE course_id = $1 AND student_id = $2 AND enrollment_status <> 'dropped'",
+        course_id,
+        student.sub
+    )
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("No estás inscrito en este curso".into()));
+    }
+    if course.is_some() {
+        promote_waitlisted(&mut tx, course_id).await?;
+    }
+    tx.commit().await?;
+
+    tracing::info!(course_id = %course_id, student_id = %student.sub, "Estudiante dado de baja");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/courses",
+    params(EnrollmentsQuery),
+    responses(
+        (status = 200, description = "Cursos en los que está inscrito el usuario, los más recientes primero. Por defecto incluye también las bajas", body = Vec<EnrolledCourse>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_my_courses(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<EnrollmentsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rows = sqlx::query!(
+        "SELECT c.id, c.instructor_id, c.course_name, c.course_slug, c.course_description,
+                c.course_status as \"course_status: CourseStatus\",
+                c.course_visibility as \"course_visibility: CourseVisibility\",
+                c.course_category, c.max_enrollments, c.enrollment_opens_at, c.enrollment_closes_at,
+                c.course_published_at, c.course_created_at,
+                e.enrollment_status as \"enrollment_status: EnrollmentStatus\",
+                e.enrolled_at, e.completed_at, e.dropped_at,
+                CASE WHEN e.enrollment_status = 'waitlisted' THEN (
+                    SELECT COUNT(*) + 1 FROM enrollments w
+                    WHERE w.course_id = e.course_id AND w.enrollment_status = 'waitlisted'
+                      AND (w.waitlisted_at, w.id) < (e.waitlisted_at, e.id)
+                ) END AS waitlist_position
+         FROM enrollments e JOIN courses c ON c.id = e.course_id
+         WHERE e.student_id = $1
+           AND ($2::enrollment_status IS NULL OR e.enrollment_status = $2)
+           AND c.course_deleted_at IS NULL
+           AND c.course_status IN ('published', 'archived')
+         ORDER BY e.enrolled_at DESC, c.id",
+        claims.sub,
+        query.status as Option<EnrollmentStatus>
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    let courses: Vec<EnrolledCourse> = rows
+        .into_iter()
+        .map(|row| EnrolledCourse {
+            course: Course {
+                id: row.id,
+                instructor_id: row.instructor_id,
+                course_name: row.course_name,
+                course_slug: row.course_slug,
+                course_description: row.course_description,
+                course_status: row.course_status,
+                course_visibility: row.course_visibility,
+                course_category: row.course_category,
+                max_enrollments: row.max_enrollments,
+                enrollment_opens_at: row.enrollment_opens_at,
+                enrollment_closes_at: row.enrollment_closes_at,
+                course_published_at: row.course_published_at,
+                course_created_at: row.course_created_at,
+            },
+            enrollment_status: row.enrollment_status,
+            enrolled_at: row.enrolled_at,
+            completed_at: row.completed_at,
+            dropped_at: row.dropped_at,
+            waitlist_position: row.waitlist_position,
+        })
+        .collect();
+
+    Ok(Json(courses))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses/{id}/enrollments",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso"),
+        EnrollmentsQuery
+    ),
+    responses(
+        (status = 200, description = "Estudiantes inscritos, por fecha de inscripción, y al final la lista de espera por orden de llegada (el mismo orden en que se promueve)", body = Vec<Enrollment>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_course_enrollments(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+    Query(query): Query<EnrollmentsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let instructor_id = course_instructor(&state.db_pool, course_id).await?;
+    ensure_can_manage(&claims, instructor_id)?;
+
+    let enrollments = sqlx::query_as!(
+        Enrollment,
+        "SELECT course_id, student_id, enrollment_status as \"enrollment_status: _\",
+                enrolled_at, completed_at, dropped_at, waitlisted_at
+         FROM enrollments
+         WHERE course_id = $1 AND ($2::enrollment_status IS NULL OR enrollment_status = $2)
+         ORDER BY enrollment_status = 'waitlisted',
+                  CASE WHEN enrollment_status = 'waitlisted' THEN waitlisted_at ELSE enrolled_at END,
+                  id",
+        course_id,
+        query.status as Option<EnrollmentStatus>
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(enrollments))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/courses/{id}/enrollments/{student_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso"),
+        ("student_id" = Uuid, Path, description = "ID del estudiante")
+    ),
+    request_body = UpdateEnrollment,
+    responses(
+        (status = 200, description = "Estado de la inscripción actualizado (por ejemplo, para marcar el curso como completado). Si se libera una plaza, pasa al primero de la lista de espera", body = Enrollment),
+        (status = 400, description = "Se pidió `waitlisted`: la lista de espera la gestiona el servicio", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado o el estudiante no está inscrito", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "No quedan plazas para pasar la inscripción a `active` o `completed`", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_enrollment(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((course_id, student_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<UpdateEnrollment>,
+) -> Result<impl IntoResponse, ApiError> {
+    if payload.enrollment_status == EnrollmentStatus::Waitlisted {
+        return Err(ApiError::BadRequest(
+            "La lista de espera se gestiona automáticamente; no se puede asignar `waitlisted`".into(),
+        ));
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+    let course = lock_course(&mut tx, course_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+    ensure_can_manage(&claims, course.instructor_id)?;
+
+    let current = sqlx::query_scalar!(
+        "SELECT enrollment_status as \"enrollment_status: EnrollmentStatus\"
+         FROM enrollments WHERE course_id = $1 AND student_id = $2",
+        course_id,
+        student_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El estudiante no está inscrito en el curso".into()))?;
+
+    // Activos y completados ocupan plaza: pasar a cualquiera de los dos desde una
+    // baja o la lista de espera necesita una libre (no se salta la lista)
+    let holds_seat = |status| matches!(status, EnrollmentStatus::Active | EnrollmentStatus::Completed);
+    let takes_seat = !holds_seat(current) && holds_seat(payload.enrollment_status);
+    if takes_seat && seats_available(&mut tx, course_id).await? == 0 {
+        return Err(ApiError::Conflict("El curso no tiene plazas libres".into()));
+    }
+
+    // Las fechas de finalización y baja se fijan al entrar en ese estado y se
+    // conservan si el estado no cambia
+    let enrollment = sqlx::query_as!(
+        Enrollment,
+        "UPDATE enrollments SET
+            enrollment_status = $3,
+            enrolled_at = CASE WHEN $4 THEN NOW() ELSE enrolled_at END,
+            completed_at = CASE
+                WHEN $3::enrollment_status = 'completed' THEN COALESCE(completed_at, NOW())
+                WHEN $3::enrollment_status = 'active' THEN NULL
+                ELSE completed_at
+            END,
+            dropped_at = CASE
+                WHEN $3::enrollment_status = 'dropped' THEN COALESCE(dropped_at, NOW())
+                ELSE NULL
+            END,
+            enrollment_updated_at = NOW()
+         WHERE course_id = $1 AND student_id = $2
+         RETURNING course_id, student_id, enrollment_status as \"enrollment_status: _\",
+                   enrolled_at, completed_at, dropped_at, waitlisted_at",
+        course_id,
+        student_id,
+        payload.enrollment_status as EnrollmentStatus,
+        takes_seat && payload.enrollment_status == EnrollmentStatus::Active
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    promote_waitlisted(&mut tx, course_id).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        "Inscripción de {} en el curso {} pasó de `{:?}` a `{:?}` por {}",
+        student_id,
+        course_id,
+        current,
+        enrollment.enrollment_status,
+        claims.sub
+    );
+    Ok(Json(enrollment))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/courses/{id}/enrollment-settings",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    request_body = EnrollmentSettings,
+    responses(
+        (status = 200, description = "Cupo y periodo de inscripción actualizados. Si hay más plazas, se promueve a los primeros de la lista de espera; si hay menos, nadie pierde la suya", body = Course),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_enrollment_settings(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<EnrollmentSettings>,
+) -> Result<impl IntoResponse, ApiError> {
+    if let (Some(opens), Some(closes)) = (payload.enrollment_opens_at, payload.enrollment_closes_at) {
+        if opens >= closes {
+            let mut errors = ValidationErrors::new();
+            errors.add(
+                "enrollment_closes_at",
+                ValidationError::new("range").with_message("Debe ser posterior a `enrollment_opens_at`".into()),
+            );
+            return Err(errors.into());
+        }
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+    let course = lock_course(&mut tx, course_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+    ensure_can_manage(&claims, course.instructor_id)?;
+
+    let updated = sqlx::query_as!(
+        Course,
+        "UPDATE courses SET
+            max_enrollments = $2,
+            enrollment_opens_at = $3,
+            enrollment_closes_at = $4,
+            course_updated_at = NOW()
+         WHERE id = $1
+         RETURNING id, instructor_id, course_name, course_slug, course_description,
+                   course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                   course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                   course_published_at, course_created_at",
+        course_id,
+        payload.max_enrollments,
+        payload.enrollment_opens_at,
+        payload.enrollment_closes_at
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    let promoted = promote_waitlisted(&mut tx, course_id).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        course_id = %course_id,
+        max_enrollments = ?updated.max_enrollments,
+        promoted,
+        updated_by = %claims.sub,
+        "Cupo de inscripción actualizado"
+    );
+    Ok(Json(updated))
+}
+
+/// Inscribe al estudiante, o lo pone en la lista de espera si el curso está lleno.
+/// Bloquea el curso hasta el final de la transacción. Devuelve `None` si ya estaba
+/// inscrito, en espera o completó el curso; una baja anterior se reactiva.
+pub async fn enroll_student(
+    conn: &mut PgConnection,
+    course_id: Uuid,
+    student_id: Uuid,
+    source: EnrollmentSource,
+) -> Result<Option<Enrollment>, ApiError> {
+    let course = lock_course(conn, course_id)
+        .await?
+        .filter(|c| c.course_status.is_visible_to_students())
+        .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+    if course.course_status == CourseStatus::Archived {
+        return Err(ApiError::Conflict("El curso está archivado y no admite inscripciones".into()));
+    }
+    if course.course_visibility == CourseVisibility::Private && source == EnrollmentSource::SelfService {
+        return Err(ApiError::Forbidden(
+            "El curso es privado: hace falta un código de inscripción o una invitación".into(),
+        ));
+    }
+    if source != EnrollmentSource::Instructor {
+        let now = Utc::now();
+        if course.enrollment_opens_at.is_some_and(|opens| now < opens) {
+            return Err(ApiError::Conflict("La inscripción en este curso todavía no está abierta".into()));
+        }
+        if course.enrollment_closes_at.is_some_and(|closes| now >= closes) {
+            return Err(ApiError::Conflict("La inscripción en este curso ya cerró".into()));
+        }
+    }
+
+    // Cupo y lista de espera: `enroll_in_course` (ver las migraciones)
+    let enrollment = sqlx::query_as!(
+        Enrollment,
+        "SELECT course_id as \"course_id!\", student_id as \"student_id!\",
+                enrollment_status as \"enrollment_status!: _\", enrolled_at as \"enrolled_at!\",
+                completed_at, dropped_at, waitlisted_at
+         FROM enroll_in_course($1, $2)",
+        course_id,
+        student_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    if let Some(enrollment) = &enrollment {
+        if enrollment.enrollment_status == EnrollmentStatus::Waitlisted {
+            tracing::info!(course_id = %course_id, student_id = %student_id, ?source, "Estudiante en lista de espera");
+        } else {
+            tracing::info!(course_id = %course_id, student_id = %student_id, ?source, "Estudiante inscrito");
+        }
+    }
+    Ok(enrollment)
+}
+
+/// Indica si el estudiante tiene acceso a las lecciones del curso: está inscrito
+/// y no se dio de baja.
+pub async fn has_lesson_access(db_pool: &PgPool, course_id: Uuid, student_id: Uuid) -> Result<bool, ApiError> {
+    let enrolled = sqlx::query_scalar!(
+        "SELECT EXISTS (
+            SELECT 1 FROM enrollments
+            WHERE course_id = $1 AND student_id = $2 AND enrollment_status IN ('active', 'completed')
+         ) AS \"exists!\"",
+        course_id,
+        student_id
+    )
+    .fetch_one(db_pool)
+    .await?;
+    Ok(enrolled)
+}
+
+/// Bloquea la fila del curso hasta el final de la transacción. Las inscripciones,
+/// bajas y cambios de cupo de un curso se serializan con este bloqueo.
+async fn lock_course(conn: &mut PgConnection, course_id: Uuid) -> Result<Option<LockedCourse>, ApiError> {
+    let course = sqlx::query_as!(
+        LockedCourse,
+        "SELECT instructor_id, course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                enrollment_opens_at, enrollment_closes_at
+         FROM courses WHERE id = $1 AND course_deleted_at IS NULL
+         FOR UPDATE",
+        course_id
+    )
+    .fetch_optional(conn)
+    .await?;
+    Ok(course)
+}
+
+/// Plazas libres del curso (`i64::MAX` si no tiene límite), según
+/// `course_free_seats`. Requiere el curso bloqueado.
+async fn seats_available(conn: &mut PgConnection, course_id: Uuid) -> Result<i64, ApiError> {
+    let free = sqlx::query_scalar!("SELECT course_free_seats($1)", course_id)
+        .fetch_one(conn)
+        .await?;
+    Ok(free.unwrap_or(i64::MAX))
+}
+
+/// Ocupa las plazas libres con los primeros de la lista de espera. Requiere el
+/// curso bloqueado; devuelve cuántos estudiantes se promovieron.
+async fn promote_waitlisted(conn: &mut PgConnection, course_id: Uuid) -> Result<u64, ApiError> {
+    let free = seats_available(conn, course_id).await?;
+    if free == 0 {
+        return Ok(0);
+    }
+
+    let promoted = sqlx::query_scalar!(
+        "UPDATE enrollments SET
+            enrollment_status = 'active',
+            enrolled_at = NOW(),
+            enrollment_updated_at = NOW()
+         WHERE id IN (
+            SELECT id FROM enrollments
+            WHERE course_id = $1 AND enrollment_status = 'waitlisted'
+            ORDER BY waitlisted_at, id
+            LIMIT $2
+         )
+         RETURNING student_id",
+        course_id,
+        free
+    )
+    .fetch_all(conn)
+    .await?;
+
+    for student_id in &promoted {
+        tracing::info!(course_id = %course_id, student_id = %student_id, "Estudiante promovido desde la lista de espera");
+    }
+    Ok(promoted.len() as u64)
+}
+
+/// Con la política de verificación activa, solo se inscribe quien verificó su email.
+pub fn ensure_verified_email(state: &AppState, claims: &Claims) -> Result<(), ApiError> {
+    if state.require_verified_email && !claims.email_verified {
+        return Err(ApiError::Forbidden("Verifica tu email antes de inscribirte en un curso".into()));
+    }
+    Ok(())
+}
+
+/// Solo el instructor del curso o un administrador gestionan sus inscripciones.
+pub fn ensure_can_manage(claims: &Claims, instructor_id: Uuid) -> Result<(), ApiError> {
+    if !can_manage_course(Some(claims), instructor_id) {
+        return Err(ApiError::Forbidden(
+            "Solo el instructor del curso o un administrador puede gestionar sus inscripciones".into(),
+        ));
+    }
+    Ok(())
+}
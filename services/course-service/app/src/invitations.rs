@@ -0,0 +1,771 @@
+// --- Códigos de inscripción e invitaciones ---
+//
+// Son la forma de entrar a los cursos privados (y sirven también para los demás):
+// - Códigos: el instructor genera un código, con usos y vencimiento opcionales, y lo
+//   comparte; el estudiante lo canjea con `POST /api/v1/enrollment-codes/redeem`.
+// - Invitaciones: el instructor invita por email y el correo trae un enlace para
+//   aceptarla (`POST /api/v1/invitations/accept`). Si el email todavía no tiene
+//   cuenta, la invitación queda pendiente y se reclama sola cuando un estudiante
+//   verifica esa dirección: identity-service llama a `claim_course_invitations`
+//   (ver las migraciones). Registrarse con el email no basta.
+// - Importación: `POST /api/v1/courses/{id}/roster` recibe un CSV de emails, inscribe
+//   a los estudiantes que ya tienen cuenta e invita al resto.
+// Todas respetan el cupo del curso: con el curso lleno, el estudiante va a la lista
+// de espera.
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::{Validate, ValidateEmail, ValidationError};
+
+use lms_common::auth::roles::Student;
+use lms_common::mail::{self, Email};
+use lms_common::tokens::{generate_token, hash_token};
+use lms_common::validation::ValidatedJson;
+use lms_common::{ApiError, Claims, ProblemDetails, RequireRole, Role};
+
+use crate::enrollments::{self, Enrollment, EnrollmentSource, EnrollmentStatus};
+use crate::lifecycle::CourseStatus;
+use crate::{course_instructor, AppState};
+
+/// Sin caracteres que se confunden al dictarlos o copiarlos (0/O, 1/I/L).
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const CODE_LEN: usize = 10;
+const MAX_INVITATIONS: usize = 100;
+const MAX_ROSTER_ROWS: usize = 1000;
+
+/// Código de inscripción de un curso.
+#[derive(Serialize, ToSchema)]
+pub struct EnrollmentCode {
+    id: Uuid,
+    course_id: Uuid,
+    #[schema(example = "K7MQ2XWD9P")]
+    code: String,
+    /// Usos permitidos; `null` si no hay límite.
+    max_uses: Option<i32>,
+    /// Veces que se canjeó.
+    uses: i32,
+    expires_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Payload para crear un código de inscripción.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct CreateEnrollmentCode {
+    /// Usos permitidos (sin límite si se omite).
+    #[schema(example = 30, minimum = 1)]
+    #[validate(range(min = 1, message = "Debe ser al menos 1"))]
+    max_uses: Option<i32>,
+    /// Vencimiento (sin vencimiento si se omite).
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Payload para canjear un código de inscripción.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct RedeemEnrollmentCode {
+    #[schema(example = "K7MQ2XWD9P")]
+    #[validate(length(min = 1, max = 32, message = "Debe tener entre 1 y 32 caracteres"))]
+    code: String,
+}
+
+/// Invitación por email a un curso.
+#[derive(Serialize, ToSchema)]
+pub struct CourseInvitation {
+    id: Uuid,
+    course_id: Uuid,
+    #[schema(example = "estudiante@example.com")]
+    email: String,
+    invited_by: Uuid,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    accepted_at: Option<DateTime<Utc>>,
+    /// Usuario que la aceptó o la reclamó al verificar su email.
+    accepted_by: Option<Uuid>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Payload para invitar por email.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct CreateInvitations {
+    /// Emails a invitar (máximo 100). Reinvitar un email con una invitación pendiente
+    /// la renueva con un enlace nuevo.
+    #[validate(
+        length(min = 1, max = 100, message = "Debe contener entre 1 y 100 emails"),
+        custom(function = "valid_emails")
+    )]
+    emails: Vec<String>,
+}
+
+/// Payload para aceptar una invitación.
+#[derive(Deserialize, ToSchema)]
+pub struct AcceptInvitation {
+    /// Token del enlace recibido por email.
+    token: String,
+}
+
+/// Filtro del listado de invitaciones.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InvitationsQuery {
+    /// Solo las pendientes (ni aceptadas ni revocadas).
+    pending: Option<bool>,
+}
+
+/// Fila del CSV que no se pudo importar.
+#[derive(Serialize, ToSchema)]
+pub struct RosterRowError {
+    /// Número de línea en el archivo, empezando en 1.
+    line: usize,
+    value: String,
+    reason: String,
+}
+
+/// Resultado de importar un CSV de emails.
+#[derive(Default, Serialize, ToSchema)]
+pub struct RosterImport {
+    /// Estudiantes inscritos.
+    enrolled: Vec<String>,
+    /// Estudiantes que quedaron en la lista de espera por falta de plazas.
+    waitlisted: Vec<String>,
+    /// Ya estaban inscritos, en espera o habían completado el curso.
+    already_enrolled: Vec<String>,
+    /// Emails sin cuenta: se les envió una invitación que se reclama al verificar el email.
+    invited: Vec<String>,
+    /// Filas con un email inválido o de una cuenta que no es de estudiante.
+    errors: Vec<RosterRowError>,
+}
+
+/// Invitación lista para enviar por correo una vez confirmada la transacción.
+struct PendingEmail {
+    email: String,
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+// --- Códigos de inscripción ---
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/enrollment-codes",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    request_body = CreateEnrollmentCode,
+    responses(
+        (status = 201, description = "Código creado", body = EnrollmentCode),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_enrollment_code(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateEnrollmentCode>,
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_can_manage(&state.db_pool, &claims, course_id).await?;
+
+    let code = sqlx::query_as!(
+        EnrollmentCode,
+        "INSERT INTO course_enrollment_codes (course_id, code, max_uses, expires_at, created_by)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, course_id, code, max_uses, uses, expires_at, created_at, revoked_at",
+        course_id,
+        generate_code(),
+        payload.max_uses,
+        payload.expires_at,
+        claims.sub
+    )
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    tracing::info!(course_id = %course_id, code_id = %code.id, created_by = %claims.sub, "Código de inscripción creado");
+    Ok((StatusCode::CREATED, Json(code)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses/{id}/enrollment-codes",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 200, description = "Códigos del curso, los más recientes primero (incluye los vencidos y revocados)", body = Vec<EnrollmentCode>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_enrollment_codes(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_can_manage(&state.db_pool, &claims, course_id).await?;
+
+    let codes = sqlx::query_as!(
+        EnrollmentCode,
+        "SELECT id, course_id, code, max_uses, uses, expires_at, created_at, revoked_at
+         FROM course_enrollment_codes
+         WHERE course_id = $1
+         ORDER BY created_at DESC",
+        course_id
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(codes))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/courses/{id}/enrollment-codes/{code_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso"),
+        ("code_id" = Uuid, Path, description = "ID del código")
+    ),
+    responses(
+        (status = 204, description = "Código revocado; las inscripciones hechas con él se mantienen"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso o código no encontrados (o código ya revocado)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_enrollment_code(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((course_id, code_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_can_manage(&state.db_pool, &claims, course_id).await?;
+
+    let result = sqlx::query!(
+        "UPDATE course_enrollment_codes SET revoked_at = NOW()
+         WHERE id = $1 AND course_id = $2 AND revoked_at IS NULL",
+        code_id,
+        course_id
+    )
+    .execute(&state.db_pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("El código no existe".into()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/enrollment-codes/redeem",
+    request_body = RedeemEnrollmentCode,
+    responses(
+        (status = 201, description = "Inscripción en el curso del código (en `waitlisted` si está lleno)", body = Enrollment),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es estudiante o no verificó su email)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "El código no existe o fue revocado, o su curso no está publicado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El código venció o agotó sus usos, el estudiante ya está inscrito, o la inscripción no está abierta", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn redeem_enrollment_code(
+    State(state): State<AppState>,
+    student: RequireRole<Student>,
+    ValidatedJson(payload): ValidatedJson<RedeemEnrollmentCode>,
+) -> Result<impl IntoResponse, ApiError> {
+    enrollments::ensure_verified_email(&state, &student)?;
+
+    let mut tx = state.db_pool.begin().await?;
+
+    // El bloqueo del código evita que dos canjes simultáneos superen `max_uses`
+    let code = sqlx::query!(
+        "SELECT id, course_id, max_uses, uses, expires_at
+         FROM course_enrollment_codes
+         WHERE code = upper($1) AND revoked_at IS NULL
+         FOR UPDATE",
+        payload.code.trim()
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El código de inscripción no existe".into()))?;
+    if code.expires_at.is_some_and(|expires| expires <= Utc::now()) {
+        return Err(ApiError::Conflict("El código de inscripción venció".into()));
+    }
+    if code.max_uses.is_some_and(|max| code.uses >= max) {
+        return Err(ApiError::Conflict("El código de inscripción ya no tiene usos disponibles".into()));
+    }
+
+    let enrollment = enrollments::enroll_student(&mut tx, code.course_id, student.sub, EnrollmentSource::Code)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Ya estás inscrito en este curso o en su lista de espera".into()))?;
+    sqlx::query!("UPDATE course_enrollment_codes SET uses = uses + 1 WHERE id = $1", code.id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(enrollment)))
+}
+
+// --- Invitaciones por email ---
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/invitations",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    request_body = CreateInvitations,
+    responses(
+        (status = 201, description = "Invitaciones creadas; los correos se envían en segundo plano", body = Vec<CourseInvitation>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El curso no está publicado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_invitations(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateInvitations>,
+) -> Result<impl IntoResponse, ApiError> {
+    let course_name = ensure_can_invite(&state.db_pool, &claims, course_id).await?;
+
+    let mut seen = HashSet::new();
+    let mut invitations = Vec::new();
+    let mut pending = Vec::new();
+    let mut tx = state.db_pool.begin().await?;
+    for email in payload.emails.iter().map(|e| e.trim().to_lowercase()) {
+        if !seen.insert(email.clone()) {
+            continue;
+        }
+        let (invitation, token) = upsert_invitation(&mut tx, &state, course_id, &email, claims.sub).await?;
+        pending.push(PendingEmail {
+            email,
+            token,
+            expires_at: invitation.expires_at,
+        });
+        invitations.push(invitation);
+    }
+    tx.commit().await?;
+
+    tracing::info!(course_id = %course_id, invited = invitations.len(), invited_by = %claims.sub, "Invitaciones enviadas");
+    for invitation in pending {
+        send_invitation_email(&state, &course_name, invitation);
+    }
+    Ok((StatusCode::CREATED, Json(invitations)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses/{id}/invitations",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso"),
+        InvitationsQuery
+    ),
+    responses(
+        (status = 200, description = "Invitaciones del curso, las más recientes primero", body = Vec<CourseInvitation>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+    Query(query): Query<InvitationsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_can_manage(&state.db_pool, &claims, course_id).await?;
+
+    let invitations = sqlx::query_as!(
+        CourseInvitation,
+        "SELECT id, course_id, email, invited_by, created_at, expires_at, accepted_at, accepted_by, revoked_at
+         FROM course_invitations
+         WHERE course_id = $1
+           AND (NOT $2 OR (accepted_at IS NULL AND revoked_at IS NULL))
+         ORDER BY created_at DESC, email",
+        course_id,
+        query.pending.unwrap_or(false)
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(invitations))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/courses/{id}/invitations/{invitation_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso"),
+        ("invitation_id" = Uuid, Path, description = "ID de la invitación")
+    ),
+    responses(
+        (status = 204, description = "Invitación revocada; su enlace deja de funcionar"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado o la invitación no está pendiente", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_invitation(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((course_id, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_can_manage(&state.db_pool, &claims, course_id).await?;
+
+    let result = sqlx::query!(
+        "UPDATE course_invitations SET revoked_at = NOW()
+         WHERE id = $1 AND course_id = $2 AND accepted_at IS NULL AND revoked_at IS NULL",
+        invitation_id,
+        course_id
+    )
+    .execute(&state.db_pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("La invitación no existe o ya no está pendiente".into()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/invitations/accept",
+    request_body = AcceptInvitation,
+    responses(
+        (status = 201, description = "Inscripción en el curso de la invitación (en `waitlisted` si está lleno)", body = Enrollment),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es estudiante o la invitación es para otro email)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "La invitación no existe, ya se usó o fue revocada", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "La invitación venció o el estudiante ya está inscrito", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    student: RequireRole<Student>,
+    Json(payload): Json<AcceptInvitation>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    let invitation = sqlx::query!(
+        "SELECT id, course_id, email, expires_at
+         FROM course_invitations
+         WHERE token_hash = $1 AND accepted_at IS NULL AND revoked_at IS NULL
+         FOR UPDATE",
+        hash_token(&payload.token)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("La invitación no existe o ya se usó".into()))?;
+    if invitation.expires_at <= Utc::now() {
+        return Err(ApiError::Conflict("La invitación venció; pide al instructor que te invite de nuevo".into()));
+    }
+
+    // El enlace llegó a ese email, así que no hace falta exigir además que esté verificado
+    let user_email = sqlx::query_scalar!("SELECT email FROM users WHERE id = $1", student.sub)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    if user_email != invitation.email {
+        return Err(ApiError::Forbidden("La invitación es para otra dirección de email".into()));
+    }
+
+    let enrollment = enrollments::enroll_student(&mut tx, invitation.course_id, student.sub, EnrollmentSource::Instructor)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Ya estás inscrito en este curso o en su lista de espera".into()))?;
+    sqlx::query!(
+        "UPDATE course_invitations SET accepted_at = NOW(), accepted_by = $2 WHERE id = $1",
+        invitation.id,
+        student.sub
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(enrollment)))
+}
+
+// --- Importación de estudiantes ---
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/roster",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    request_body(
+        content = String,
+        content_type = "text/csv",
+        description = "Un email por línea. Si la primera línea tiene una columna `email`, se toma esa columna; si no, la primera. Acepta `,` o `;` como separador (máximo 1000 emails)"
+    ),
+    responses(
+        (status = 200, description = "Resultado por email: inscritos, en lista de espera, ya inscritos, invitados (sin cuenta) y filas con errores. Las inscripciones no dependen del periodo de inscripción, pero sí del cupo", body = RosterImport),
+        (status = 400, description = "El archivo está vacío o tiene más de 1000 emails", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El curso no está publicado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn import_roster(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+    body: String,
+) -> Result<impl IntoResponse, ApiError> {
+    let course_name = ensure_can_invite(&state.db_pool, &claims, course_id).await?;
+
+    let (emails, errors) = parse_roster(&body);
+    if emails.is_empty() && errors.is_empty() {
+        return Err(ApiError::BadRequest("El archivo no contiene emails".into()));
+    }
+    if emails.len() + errors.len() > MAX_ROSTER_ROWS {
+        return Err(ApiError::BadRequest(format!(
+            "El archivo admite como máximo {MAX_ROSTER_ROWS} emails"
+        )));
+    }
+
+    let mut result = RosterImport {
+        errors,
+        ..Default::default()
+    };
+    let mut pending = Vec::new();
+    let mut tx = state.db_pool.begin().await?;
+
+    let addresses: Vec<String> = emails.iter().map(|(_, email)| email.clone()).collect();
+    let users = sqlx::query!(
+        "SELECT id, email, role as \"role: Role\" FROM users WHERE email = ANY($1)",
+        &addresses
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for (line, email) in emails {
+        match users.iter().find(|u| u.email == email) {
+            Some(user) if user.role != Role::Student => result.errors.push(RosterRowError {
+                line,
+                value: email,
+                reason: "La cuenta no es de estudiante".into(),
+            }),
+            Some(user) => {
+                let enrollment =
+                    enrollments::enroll_student(&mut tx, course_id, user.id, EnrollmentSource::Instructor).await?;
+                match enrollment.map(|e| e.enrollment_status) {
+                    Some(EnrollmentStatus::Waitlisted) => result.waitlisted.push(email),
+                    Some(_) => result.enrolled.push(email),
+                    None => result.already_enrolled.push(email),
+                }
+            }
+            None => {
+                let (invitation, token) = upsert_invitation(&mut tx, &state, course_id, &email, claims.sub).await?;
+                pending.push(PendingEmail {
+                    email: email.clone(),
+                    token,
+                    expires_at: invitation.expires_at,
+                });
+                result.invited.push(email);
+            }
+        }
+    }
+    tx.commit().await?;
+    result.errors.sort_by_key(|e| e.line);
+
+    tracing::info!(
+        course_id = %course_id,
+        enrolled = result.enrolled.len(),
+        waitlisted = result.waitlisted.len(),
+        invited = result.invited.len(),
+        errors = result.errors.len(),
+        imported_by = %claims.sub,
+        "Importación de estudiantes"
+    );
+    for invitation in pending {
+        send_invitation_email(&state, &course_name, invitation);
+    }
+    Ok(Json(result))
+}
+
+/// Extrae los emails del CSV con su número de línea, sin repetir, y las filas inválidas.
+fn parse_roster(csv: &str) -> (Vec<(usize, String)>, Vec<RosterRowError>) {
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .peekable();
+    let fields = |line: &str| -> Vec<String> {
+        line.split([',', ';'])
+            .map(|f| f.trim().trim_matches('"').trim().to_lowercase())
+            .collect()
+    };
+
+    // Cabecera opcional: si tiene una columna `email`, se usa esa columna
+    let mut column = 0;
+    if let Some(index) = lines.peek().and_then(|(_, first)| fields(first).iter().position(|f| f == "email")) {
+        column = index;
+        lines.next();
+    }
+
+    let mut seen = HashSet::new();
+    let mut emails = Vec::new();
+    let mut errors = Vec::new();
+    for (line, text) in lines {
+        let value = fields(text).into_iter().nth(column).unwrap_or_default();
+        if !(value.validate_email() && value.len() <= 255) {
+            errors.push(RosterRowError {
+                line,
+                value,
+                reason: "No es un email válido".into(),
+            });
+        } else if seen.insert(value.clone()) {
+            emails.push((line, value));
+        }
+    }
+    (emails, errors)
+}
+
+// --- Auxiliares ---
+
+/// Crea la invitación pendiente de `email`, o la renueva con un token y un plazo
+/// nuevos si ya había una. Devuelve la invitación y el token en claro.
+async fn upsert_invitation(
+    conn: &mut PgConnection,
+    state: &AppState,
+    course_id: Uuid,
+    email: &str,
+    invited_by: Uuid,
+) -> Result<(CourseInvitation, String), ApiError> {
+    let token = generate_token();
+    let invitation = sqlx::query_as!(
+        CourseInvitation,
+        "INSERT INTO course_invitations (course_id, email, token_hash, invited_by, expires_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (course_id, email) WHERE accepted_at IS NULL AND revoked_at IS NULL DO UPDATE SET
+            token_hash = EXCLUDED.token_hash,
+            invited_by = EXCLUDED.invited_by,
+            created_at = NOW(),
+            expires_at = EXCLUDED.expires_at
+         RETURNING id, course_id, email, invited_by, created_at, expires_at, accepted_at, accepted_by, revoked_at",
+        course_id,
+        email,
+        hash_token(&token),
+        invited_by,
+        Utc::now() + Duration::days(state.invitation_ttl_days)
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok((invitation, token))
+}
+
+/// Envía (en segundo plano) el enlace para aceptar la invitación.
+fn send_invitation_email(state: &AppState, course_name: &str, invitation: PendingEmail) {
+    mail::send_in_background(
+        state.mailer.clone(),
+        Email {
+            to: invitation.email.clone(),
+            subject: format!("Invitación al curso «{course_name}»"),
+            body: format!(
+                "Te invitaron al curso «{}».\n\n\
+                 Acepta la invitación con este enlace (válido hasta el {}):\n{}/invitations/accept?token={}\n\n\
+                 Si aún no tienes cuenta, regístrate con este mismo email ({}) y quedarás inscrito automáticamente.",
+                course_name,
+                invitation.expires_at.format("%d/%m/%Y"),
+                state.app_base_url,
+                invitation.token,
+                invitation.email
+            ),
+        },
+    );
+}
+
+/// Solo el instructor del curso o un administrador gestionan sus códigos e invitaciones.
+async fn ensure_can_manage(db_pool: &PgPool, claims: &Claims, course_id: Uuid) -> Result<(), ApiError> {
+    let instructor_id = course_instructor(db_pool, course_id).await?;
+    enrollments::ensure_can_manage(claims, instructor_id)
+}
+
+/// Como `ensure_can_manage`, pero además exige que el curso esté publicado para que
+/// los invitados puedan inscribirse. Devuelve el nombre del curso para los correos.
+async fn ensure_can_invite(db_pool: &PgPool, claims: &Claims, course_id: Uuid) -> Result<String, ApiError> {
+    let course = sqlx::query!(
+        "SELECT instructor_id, course_name, course_status as \"course_status: CourseStatus\"
+         FROM courses WHERE id = $1 AND course_deleted_at IS NULL",
+        course_id
+    )
+    .fetch_optional(db_pool)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+    enrollments::ensure_can_manage(claims, course.instructor_id)?;
+    if course.course_status != CourseStatus::Published {
+        return Err(ApiError::Conflict("Publica el curso antes de invitar estudiantes".into()));
+    }
+    Ok(course.course_name)
+}
+
+fn generate_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..CODE_LEN)
+        .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+fn valid_emails(emails: &[String]) -> Result<(), ValidationError> {
+    if emails.len() > MAX_INVITATIONS {
+        return Ok(()); // Ya lo informa la regla `length`
+    }
+    if !emails.iter().all(|e| e.trim().validate_email() && e.trim().len() <= 255) {
+        return Err(ValidationError::new("email").with_message("Contiene emails inválidos".into()));
+    }
+    Ok(())
+}
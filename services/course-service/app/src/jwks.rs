@@ -0,0 +1,102 @@
+// --- Caché del JWK Set de identity-service ---
+//
+// Este servicio solo verifica tokens: obtiene las claves públicas desde
+// `/.well-known/jwks.json` de identity-service y las guarda en memoria.
+// Un `kid` desconocido (clave recién rotada) fuerza una recarga, limitada a
+// una cada `MIN_REFETCH_INTERVAL` para que tokens inventados no inunden a identity.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::DecodingKey;
+use tokio::sync::Mutex;
+
+const MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(30);
+
+pub struct JwksCache {
+    url: String,
+    client: reqwest::Client,
+    keys: RwLock<HashMap<String, DecodingKey>>,
+    last_fetch: Mutex<Option<Instant>>,
+}
+
+impl JwksCache {
+    pub fn new(identity_service_url: &str) -> Self {
+        Self {
+            url: format!("{identity_service_url}/.well-known/jwks.json"),
+            client: reqwest::Client::new(),
+            keys: RwLock::new(HashMap::new()),
+            last_fetch: Mutex::new(None),
+        }
+    }
+
+    /// Devuelve la clave pública del `kid`, recargando el JWK Set si no se conoce.
+    pub async fn decoding_key(&self, kid: &str) -> Option<DecodingKey> {
+        if let Some(key) = self.keys.read().unwrap().get(kid) {
+            return Some(key.clone());
+        }
+
+        {
+            let mut last_fetch = self.last_fetch.lock().await;
+            // Otra petición pudo haber recargado mientras esperábamos el lock.
+            if let Some(key) = self.keys.read().unwrap().get(kid) {
+                return Some(key.clone());
+            }
+            if last_fetch.is_some_and(|t| t.elapsed() < MIN_REFETCH_INTERVAL) {
+                return None;
+            }
+            *last_fetch = Some(Instant::now());
+            if let Err(e) = self.refresh().await {
+                tracing::error!("Error al obtener JWKS de identity-service: {}", e);
+            }
+        }
+
+        self.keys.read().unwrap().get(kid).cloned()
+    }
+
+    /// Reemplaza las claves en memoria por las publicadas actualmente.
+    pub async fn refresh(&self) -> Result<(), reqwest::Error> {
+        let jwks: JwkSet = self
+            .client
+            .get(&self.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let keys: HashMap<_, _> = jwks
+            .keys
+            .iter()
+            .filter_map(|jwk| {
+                let kid = jwk.common.key_id.clone()?;
+                match DecodingKey::from_jwk(jwk) {
+                    Ok(key) => Some((kid, key)),
+                    Err(e) => {
+                        tracing::warn!("Clave '{}' del JWKS ignorada: {}", kid, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        tracing::info!("JWKS actualizado: {} claves", keys.len());
+        *self.keys.write().unwrap() = keys;
+        Ok(())
+    }
+
+    /// Recarga el JWK Set periódicamente para dejar de aceptar claves retiradas.
+    pub fn spawn_refresh_task(self: std::sync::Arc<Self>, every: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.refresh().await {
+                    tracing::error!("Error al obtener JWKS de identity-service: {}", e);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,741 @@
+// --- Lecciones de un módulo ---
+//
+// Rutas anidadas en `/api/v1/modules/{id}/lessons`. Cada lección tiene un
+// contenido tipado (markdown, video, recurso embebido o quiz) guardado como JSONB.
+// El markdown se convierte a HTML y se sanea al guardarlo, así que los estudiantes
+// siempre reciben HTML seguro. Cada cambio de contenido incrementa
+// `lesson_content_version` y queda registrado en `lesson_content_versions`.
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{html, Options, Parser};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json as SqlJson;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::{Validate, ValidateUrl, ValidationError};
+
+use lms_common::auth::roles::Instructor;
+use lms_common::validation::{self, ValidatedJson};
+use lms_common::{ApiError, Claims, ProblemDetails, RequireRole};
+
+use crate::enrollments;
+use crate::modules::{ItemVisibility, ModuleAccess, PublishStatus};
+use crate::slug::slugify;
+use crate::{lock_course_for_instructor, AppState};
+
+/// Largo máximo del markdown de una lección.
+const MAX_MARKDOWN_LEN: usize = 200_000;
+
+// --- Contenido ---
+
+/// Contenido de una lección. El campo `type` indica la variante.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LessonContent {
+    /// Texto en Markdown. Se entrega también como HTML saneado en `lesson_content_html`.
+    Markdown {
+        #[schema(example = "# Variables\n\nEn Rust las variables son inmutables por defecto.")]
+        markdown: String,
+    },
+    /// Video externo (YouTube, Vimeo, un archivo MP4...).
+    Video {
+        #[schema(example = "https://www.youtube.com/watch?v=dQw4w9WgXcQ")]
+        url: String,
+        caption: Option<String>,
+    },
+    /// Recurso embebido en un iframe (presentación, simulación...). Solo HTTPS.
+    Embed {
+        #[schema(example = "https://docs.google.com/presentation/d/abc/embed")]
+        url: String,
+        title: Option<String>,
+    },
+    /// Referencia a un quiz.
+    Quiz { quiz_id: Uuid },
+}
+
+impl LessonContent {
+    /// HTML saneado para los estudiantes. Solo el markdown produce HTML; el resto de
+    /// variantes lo renderiza el cliente a partir de sus campos.
+    fn render_html(&self) -> Option<String> {
+        match self {
+            LessonContent::Markdown { markdown } => {
+                let mut options = Options::empty();
+                options.insert(Options::ENABLE_TABLES);
+                options.insert(Options::ENABLE_STRIKETHROUGH);
+                let mut unsafe_html = String::new();
+                html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+                // Elimina scripts, manejadores de eventos, `javascript:` y demás HTML peligroso
+                Some(ammonia::clean(&unsafe_html))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Valida los campos de cada variante del contenido.
+fn validate_content(content: &LessonContent) -> Result<(), ValidationError> {
+    let is_http_url = |url: &str| {
+        let lower = url.to_ascii_lowercase();
+        (lower.starts_with("https://") || lower.starts_with("http://")) && url.validate_url()
+    };
+
+    match content {
+        LessonContent::Markdown { markdown } if markdown.len() > MAX_MARKDOWN_LEN => {
+            Err(ValidationError::new("length").with_message(
+                format!("El markdown debe tener como máximo {MAX_MARKDOWN_LEN} caracteres").into(),
+            ))
+        }
+        LessonContent::Video { url, .. } if !is_http_url(url) => Err(ValidationError::new("url")
+            .with_message("La URL del video debe ser una URL http:// o https:// válida".into())),
+        LessonContent::Embed { url, .. }
+            if !(url.to_ascii_lowercase().starts_with("https://") && is_http_url(url)) =>
+        {
+            Err(ValidationError::new("url")
+                .with_message("La URL del recurso embebido debe ser una URL https:// válida".into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+// --- Estructuras de Datos ---
+
+/// Lección con su contenido.
+#[derive(Serialize, ToSchema)]
+pub struct Lesson {
+    id: Uuid,
+    module_id: Uuid,
+    lesson_name: String,
+    #[schema(example = "variables-y-mutabilidad")]
+    lesson_slug: String,
+    lesson_description: Option<String>,
+    /// Posición de la lección dentro del módulo (ascendente).
+    lesson_order: i32,
+    lesson_status: PublishStatus,
+    lesson_visibility: ItemVisibility,
+    #[schema(value_type = Option<LessonContent>)]
+    lesson_content: Option<SqlJson<LessonContent>>,
+    /// HTML saneado del contenido markdown.
+    lesson_content_html: Option<String>,
+    /// Versión vigente del contenido (0 si la lección aún no tiene contenido).
+    lesson_content_version: i32,
+    lesson_created_at: Option<DateTime<Utc>>,
+    lesson_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Lección sin contenido, para los listados.
+#[derive(Serialize, ToSchema)]
+pub struct LessonSummary {
+    id: Uuid,
+    module_id: Uuid,
+    lesson_name: String,
+    lesson_slug: String,
+    lesson_description: Option<String>,
+    lesson_order: i32,
+    lesson_status: PublishStatus,
+    lesson_visibility: ItemVisibility,
+    lesson_content_version: i32,
+}
+
+/// Payload para reordenar las lecciones de un módulo.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct ReorderLessons {
+    /// Todas las lecciones del módulo, en el orden deseado. Puede incluir lecciones de
+    /// otros módulos del mismo curso, que se mueven a este.
+    #[validate(custom(function = "validation::unique_ids"))]
+    lesson_ids: Vec<Uuid>,
+}
+
+/// Versión anterior (o vigente) del contenido de una lección.
+#[derive(Serialize, ToSchema)]
+pub struct LessonContentVersion {
+    version: i32,
+    #[schema(value_type = LessonContent)]
+    content: SqlJson<LessonContent>,
+    created_by: Uuid,
+    created_at: DateTime<Utc>,
+}
+
+/// Payload para crear una lección. Se agrega al final del módulo.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct CreateLesson {
+    #[schema(example = "Variables y mutabilidad", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
+    lesson_name: String,
+    #[schema(max_length = 10000)]
+    #[validate(length(max = 10000, message = "Debe tener como máximo 10000 caracteres"))]
+    lesson_description: Option<String>,
+    /// Por defecto `draft`.
+    lesson_status: Option<PublishStatus>,
+    /// Por defecto `visible`.
+    lesson_visibility: Option<ItemVisibility>,
+    #[validate(custom(function = "validate_content"))]
+    lesson_content: Option<LessonContent>,
+}
+
+/// Payload para actualizar una lección. Solo se modifican los campos enviados.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct UpdateLesson {
+    #[schema(example = "Variables, constantes y mutabilidad", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
+    lesson_name: Option<String>,
+    #[schema(max_length = 10000)]
+    #[validate(length(max = 10000, message = "Debe tener como máximo 10000 caracteres"))]
+    lesson_description: Option<String>,
+    lesson_status: Option<PublishStatus>,
+    lesson_visibility: Option<ItemVisibility>,
+    /// Nuevo contenido; crea una nueva versión.
+    #[validate(custom(function = "validate_content"))]
+    lesson_content: Option<LessonContent>,
+    /// Versión del contenido sobre la que se hicieron los cambios. Si otra edición
+    /// la cambió mientras tanto, se responde 409 en lugar de pisarla.
+    expected_content_version: Option<i32>,
+}
+
+// --- Handlers ---
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/modules/{id}/lessons",
+    params(
+        ("id" = Uuid, Path, description = "ID del módulo")
+    ),
+    request_body = CreateLesson,
+    responses(
+        (status = 201, description = "Lección creada al final del módulo", body = Lesson),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Módulo no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_lesson(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path(module_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateLesson>,
+) -> Result<impl IntoResponse, ApiError> {
+    let module = ModuleAccess::load(&state.db_pool, module_id).await?;
+    module.ensure_instructor(&instructor)?;
+
+    let content = payload.lesson_content.as_ref().map(serde_json::to_value).transpose().map_err(|e| {
+        tracing::error!("Error al serializar el contenido de la lección: {:?}", e);
+        ApiError::Internal
+    })?;
+    let content_html = payload.lesson_content.as_ref().and_then(LessonContent::render_html);
+
+    let mut tx = state.db_pool.begin().await?;
+    // Serializa las altas y reordenamientos del curso para que el orden no se repita
+    lock_course_for_instructor(&mut tx, module.course_id, &instructor).await?;
+
+    let lesson = sqlx::query_as!(
+        Lesson,
+        "INSERT INTO lessons (module_id, lesson_name, lesson_slug, lesson_description, lesson_order,
+                              lesson_status, lesson_visibility, lesson_content, lesson_content_html, lesson_content_version)
+         VALUES ($1, $2, $3, $4,
+                 (SELECT COALESCE(MAX(lesson_order), 0) + 1 FROM lessons WHERE module_id = $1),
+                 $5, $6, $7, $8, CASE WHEN $7::jsonb IS NULL THEN 0 ELSE 1 END)
+         RETURNING id, module_id, lesson_name, lesson_slug, lesson_description, lesson_order,
+                   lesson_status as \"lesson_status: _\", lesson_visibility as \"lesson_visibility: _\",
+                   lesson_content as \"lesson_content: SqlJson<LessonContent>\", lesson_content_html,
+                   lesson_content_version, lesson_created_at, lesson_updated_at",
+        module_id,
+        payload.lesson_name.trim(),
+        slugify(&payload.lesson_name),
+        payload.lesson_description,
+        payload.lesson_status.unwrap_or(PublishStatus::Draft) as PublishStatus,
+        payload.lesson_visibility.unwrap_or(ItemVisibility::Visible) as ItemVisibility,
+        content,
+        content_html
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if let Some(content) = &lesson.lesson_content {
+        record_version(&mut tx, lesson.id, lesson.lesson_content_version, content, instructor.sub).await?;
+    }
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(lesson)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/modules/{id}/lessons",
+    params(
+        ("id" = Uuid, Path, description = "ID del módulo")
+    ),
+    responses(
+        (status = 200, description = "Lecciones del módulo ordenadas por `lesson_order`, sin su contenido. Los borradores y ocultos solo los ve el instructor del curso o un administrador", body = Vec<LessonSummary>),
+        (status = 401, description = "Token inválido", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Módulo no encontrado (o no publicado)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        (),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_lessons(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path(module_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let module = ModuleAccess::load(&state.db_pool, module_id).await?;
+    let show_all = module.can_see_unpublished(claims.as_ref());
+    if !show_all && !module.is_public {
+        return Err(ApiError::NotFound("El módulo no existe".into()));
+    }
+
+    let lessons = sqlx::query_as!(
+        LessonSummary,
+        "SELECT id, module_id, lesson_name, lesson_slug, lesson_description, lesson_order,
+                lesson_status as \"lesson_status: _\", lesson_visibility as \"lesson_visibility: _\",
+                lesson_content_version
+         FROM lessons
+         WHERE module_id = $1
+           AND ($2 OR (lesson_status = 'published' AND lesson_visibility = 'visible'))
+         ORDER BY lesson_order, lesson_created_at",
+        module_id,
+        show_all
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(lessons))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/modules/{id}/lessons/{lesson_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del módulo"),
+        ("lesson_id" = Uuid, Path, description = "ID de la lección")
+    ),
+    responses(
+        (status = 200, description = "Lección con su contenido. Solo la ven los estudiantes inscritos en el curso, su instructor y los administradores", body = Lesson),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no está inscrito en el curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Módulo o lección no encontrados (o no publicados)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_lesson(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path((module_id, lesson_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let module = ModuleAccess::load(&state.db_pool, module_id).await?;
+    let show_all = module.can_see_unpublished(claims.as_ref());
+    if !show_all && !module.is_public {
+        return Err(ApiError::NotFound("El módulo no existe".into()));
+    }
+    if !show_all {
+        let claims = claims.ok_or(ApiError::Unauthorized)?;
+        if !enrollments::has_lesson_access(&state.db_pool, module.course_id, claims.sub).await? {
+            return Err(ApiError::Forbidden("Inscríbete en el curso para ver sus lecciones".into()));
+        }
+    }
+
+    let lesson = sqlx::query_as!(
+        Lesson,
+        "SELECT id, module_id, lesson_name, lesson_slug, lesson_description, lesson_order,
+                lesson_status as \"lesson_status: _\", lesson_visibility as \"lesson_visibility: _\",
+                lesson_content as \"lesson_content: SqlJson<LessonContent>\", lesson_content_html,
+                lesson_content_version, lesson_created_at, lesson_updated_at
+         FROM lessons
+         WHERE id = $1 AND module_id = $2
+           AND ($3 OR (lesson_status = 'published' AND lesson_visibility = 'visible'))",
+        lesson_id,
+        module_id,
+        show_all
+    )
+    .fetch_optional(&state.db_pool)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("La lección no existe".into()))?;
+
+    Ok(Json(lesson))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/modules/{id}/lessons/{lesson_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del módulo"),
+        ("lesson_id" = Uuid, Path, description = "ID de la lección")
+    ),
+    request_body = UpdateLesson,
+    responses(
+        (status = 200, description = "Lección actualizada. Si cambia el nombre se regenera el slug; si cambia el contenido se crea una nueva versión", body = Lesson),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Módulo o lección no encontrados", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "`expected_content_version` no coincide con la versión vigente", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_lesson(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path((module_id, lesson_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<UpdateLesson>,
+) -> Result<impl IntoResponse, ApiError> {
+    ModuleAccess::load(&state.db_pool, module_id)
+        .await?
+        .ensure_instructor(&instructor)?;
+
+    let content = payload.lesson_content.as_ref().map(serde_json::to_value).transpose().map_err(|e| {
+        tracing::error!("Error al serializar el contenido de la lección: {:?}", e);
+        ApiError::Internal
+    })?;
+    let content_html = payload.lesson_content.as_ref().and_then(LessonContent::render_html);
+
+    let mut tx = state.db_pool.begin().await?;
+
+    // Bloquear la lección para que dos ediciones simultáneas no generen la misma versión
+    let current_version = sqlx::query_scalar!(
+        "SELECT lesson_content_version FROM lessons WHERE id = $1 AND module_id = $2 FOR UPDATE",
+        lesson_id,
+        module_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("La lección no existe".into()))?;
+
+    if let Some(expected) = payload.expected_content_version {
+        if expected != current_version {
+            return Err(ApiError::Conflict(format!(
+                "El contenido cambió mientras lo editabas: la versión vigente es {current_version}"
+            )));
+        }
+    }
+
+    let lesson_name = payload.lesson_name.as_deref().map(str::trim);
+    let lesson = sqlx::query_as!(
+        Lesson,
+        "UPDATE lessons SET
+            lesson_name = COALESCE($2, lesson_name),
+            lesson_slug = COALESCE($3, lesson_slug),
+            lesson_description = COALESCE($4, lesson_description),
+            lesson_status = COALESCE($5, lesson_status),
+            lesson_visibility = COALESCE($6, lesson_visibility),
+            lesson_content = COALESCE($7, lesson_content),
+            lesson_content_html = CASE WHEN $7::jsonb IS NULL THEN lesson_content_html ELSE $8 END,
+            lesson_content_version = lesson_content_version + CASE WHEN $7::jsonb IS NULL THEN 0 ELSE 1 END,
+            lesson_updated_at = NOW()
+         WHERE id = $1
+         RETURNING id, module_id, lesson_name, lesson_slug, lesson_description, lesson_order,
+                   lesson_status as \"lesson_status: _\", lesson_visibility as \"lesson_visibility: _\",
+                   lesson_content as \"lesson_content: SqlJson<LessonContent>\", lesson_content_html,
+                   lesson_content_version, lesson_created_at, lesson_updated_at",
+        lesson_id,
+        lesson_name,
+        lesson_name.map(slugify),
+        payload.lesson_description,
+        payload.lesson_status as Option<PublishStatus>,
+        payload.lesson_visibility as Option<ItemVisibility>,
+        content,
+        content_html
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if payload.lesson_content.is_some() {
+        if let Some(content) = &lesson.lesson_content {
+            record_version(&mut tx, lesson.id, lesson.lesson_content_version, content, instructor.sub).await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(Json(lesson))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/modules/{id}/lessons/{lesson_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del módulo"),
+        ("lesson_id" = Uuid, Path, description = "ID de la lección")
+    ),
+    responses(
+        (status = 204, description = "Lección eliminada junto con su historial de contenido"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Módulo o lección no encontrados", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_lesson(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path((module_id, lesson_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let module = ModuleAccess::load(&state.db_pool, module_id).await?;
+    module.ensure_instructor(&instructor)?;
+
+    let mut tx = state.db_pool.begin().await?;
+    lock_course_for_instructor(&mut tx, module.course_id, &instructor).await?;
+
+    let deleted_order = sqlx::query_scalar!(
+        "DELETE FROM lessons WHERE id = $1 AND module_id = $2 RETURNING lesson_order",
+        lesson_id,
+        module_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("La lección no existe".into()))?;
+
+    // Cerrar el hueco que deja la lección
+    sqlx::query!(
+        "UPDATE lessons SET lesson_order = lesson_order - 1 WHERE module_id = $1 AND lesson_order > $2",
+        module_id,
+        deleted_order
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/modules/{id}/lessons/order",
+    params(
+        ("id" = Uuid, Path, description = "ID del módulo de destino")
+    ),
+    request_body = ReorderLessons,
+    responses(
+        (status = 200, description = "Lecciones del módulo en el nuevo orden", body = Vec<LessonSummary>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Módulo no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "La lista no coincide con las lecciones actuales (falta alguna del módulo o hay IDs que no son del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn reorder_lessons(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path(module_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<ReorderLessons>,
+) -> Result<impl IntoResponse, ApiError> {
+    let module = ModuleAccess::load(&state.db_pool, module_id).await?;
+    module.ensure_instructor(&instructor)?;
+
+    let mut tx = state.db_pool.begin().await?;
+    lock_course_for_instructor(&mut tx, module.course_id, &instructor).await?;
+
+    // Cada ID debe ser una lección del mismo curso, y no puede faltar ninguna lección del módulo
+    let listed = sqlx::query!(
+        "SELECT l.id, l.module_id
+         FROM lessons l
+         JOIN modules m ON m.id = l.module_id
+         WHERE m.course_id = $1 AND l.id = ANY($2)",
+        module.course_id,
+        &payload.lesson_ids
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let current = sqlx::query_scalar!("SELECT id FROM lessons WHERE module_id = $1", module_id)
+        .fetch_all(&mut *tx)
+        .await?;
+    let listed_ids: HashSet<Uuid> = listed.iter().map(|row| row.id).collect();
+    if listed.len() != payload.lesson_ids.len() || !current.iter().all(|id| listed_ids.contains(id)) {
+        return Err(ApiError::Conflict(
+            "La lista no coincide con las lecciones actuales del módulo; vuelve a cargarlas".into(),
+        ));
+    }
+    let source_modules: Vec<Uuid> = listed
+        .iter()
+        .map(|row| row.module_id)
+        .filter(|id| *id != module_id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    sqlx::query!(
+        "UPDATE lessons SET module_id = $1, lesson_order = t.position::int
+         FROM UNNEST($2::uuid[]) WITH ORDINALITY AS t(id, position)
+         WHERE lessons.id = t.id",
+        module_id,
+        &payload.lesson_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // Las lecciones que quedan en los módulos de origen se renumeran sin huecos
+    if !source_modules.is_empty() {
+        sqlx::query!(
+            "UPDATE lessons SET lesson_order = r.position::int
+             FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY module_id ORDER BY lesson_order) AS position
+                   FROM lessons
+                   WHERE module_id = ANY($1)) AS r
+             WHERE lessons.id = r.id AND lessons.lesson_order <> r.position",
+            &source_modules
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let lessons = sqlx::query_as!(
+        LessonSummary,
+        "SELECT id, module_id, lesson_name, lesson_slug, lesson_description, lesson_order,
+                lesson_status as \"lesson_status: _\", lesson_visibility as \"lesson_visibility: _\",
+                lesson_content_version
+         FROM lessons
+         WHERE module_id = $1
+         ORDER BY lesson_order",
+        module_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(lessons))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/modules/{id}/lessons/{lesson_id}/versions",
+    params(
+        ("id" = Uuid, Path, description = "ID del módulo"),
+        ("lesson_id" = Uuid, Path, description = "ID de la lección")
+    ),
+    responses(
+        (status = 200, description = "Historial del contenido, de la versión más reciente a la más antigua", body = Vec<LessonContentVersion>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Módulo o lección no encontrados", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_content_versions(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path((module_id, lesson_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    ModuleAccess::load(&state.db_pool, module_id)
+        .await?
+        .ensure_instructor(&instructor)?;
+
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS (SELECT 1 FROM lessons WHERE id = $1 AND module_id = $2) AS \"exists!\"",
+        lesson_id,
+        module_id
+    )
+    .fetch_one(&state.db_pool)
+    .await?;
+    if !exists {
+        return Err(ApiError::NotFound("La lección no existe".into()));
+    }
+
+    let versions = sqlx::query_as!(
+        LessonContentVersion,
+        "SELECT version, content as \"content: SqlJson<LessonContent>\", created_by, created_at
+         FROM lesson_content_versions
+         WHERE lesson_id = $1
+         ORDER BY version DESC",
+        lesson_id
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(versions))
+}
+
+/// Guarda una versión del contenido en el historial.
+async fn record_version(
+    tx: &mut sqlx::PgConnection,
+    lesson_id: Uuid,
+    version: i32,
+    content: &SqlJson<LessonContent>,
+    created_by: Uuid,
+) -> Result<(), ApiError> {
+    sqlx::query!(
+        "INSERT INTO lesson_content_versions (lesson_id, version, content, created_by)
+         VALUES ($1, $2, $3, $4)",
+        lesson_id,
+        version,
+        content as &SqlJson<LessonContent>,
+        created_by
+    )
+    .execute(tx)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(content: LessonContent) -> Option<String> {
+        validate_content(&content).err().map(|err| err.code.into_owned())
+    }
+
+    fn video(url: &str) -> LessonContent {
+        LessonContent::Video { url: url.into(), caption: None }
+    }
+
+    fn embed(url: &str) -> LessonContent {
+        LessonContent::Embed { url: url.into(), title: None }
+    }
+
+    #[test]
+    fn markdown_up_to_the_limit() {
+        assert_eq!(error_code(LessonContent::Markdown { markdown: "a".repeat(MAX_MARKDOWN_LEN) }), None);
+        assert_eq!(
+            error_code(LessonContent::Markdown { markdown: "a".repeat(MAX_MARKDOWN_LEN + 1) }),
+            Some("length".into())
+        );
+    }
+
+    #[test]
+    fn video_accepts_http_and_https() {
+        assert_eq!(error_code(video("https://www.youtube.com/watch?v=dQw4w9WgXcQ")), None);
+        assert_eq!(error_code(video("HTTP://example.com/clase.mp4")), None);
+        assert_eq!(error_code(video("ftp://example.com/clase.mp4")), Some("url".into()));
+        assert_eq!(error_code(video("javascript:alert(1)")), Some("url".into()));
+        assert_eq!(error_code(video("no es una url")), Some("url".into()));
+    }
+
+    #[test]
+    fn embed_requires_https() {
+        assert_eq!(error_code(embed("https://docs.google.com/presentation/d/abc/embed")), None);
+        assert_eq!(error_code(embed("http://docs.google.com/presentation/d/abc/embed")), Some("url".into()));
+        assert_eq!(error_code(embed("https://")), Some("url".into()));
+    }
+
+    #[test]
+    fn quiz_needs_no_checks() {
+        assert_eq!(error_code(LessonContent::Quiz { quiz_id: Uuid::nil() }), None);
+    }
+}
@@ -0,0 +1,337 @@
+// --- Ciclo de vida de los cursos ---
+//
+// Un curso nace en borrador, se envía a revisión, se publica y puede archivarse.
+// Los cambios de estado tienen su propio endpoint (`/api/v1/courses/{id}/publish`, …)
+// y solo los hace el instructor del curso o un administrador. La revisión es
+// obligatoria para el instructor: solo un administrador publica directamente desde
+// borrador. Los estudiantes solo ven cursos publicados o archivados.
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use lms_common::{ApiError, Claims, ProblemDetails, Role};
+
+use crate::{can_manage_course, AppState, Course};
+
+/// Estado de publicación de un curso. En la base de datos es el tipo `course_status`.
+#[derive(Debug, Serialize, Deserialize, ToSchema, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "course_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CourseStatus {
+    /// En preparación; solo lo ven su instructor y los administradores.
+    Draft,
+    /// Listo para publicar, pendiente de revisión.
+    Review,
+    /// Visible para los estudiantes.
+    Published,
+    /// Ya no se ofrece, pero sigue accesible para consulta.
+    Archived,
+}
+
+impl CourseStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CourseStatus::Draft => "draft",
+            CourseStatus::Review => "review",
+            CourseStatus::Published => "published",
+            CourseStatus::Archived => "archived",
+        }
+    }
+
+    /// Los estudiantes ven los cursos publicados y los archivados.
+    pub fn is_visible_to_students(self) -> bool {
+        matches!(self, CourseStatus::Published | CourseStatus::Archived)
+    }
+}
+
+/// Visibilidad de un curso. En la base de datos es el tipo `course_visibility`.
+#[derive(Debug, Serialize, Deserialize, ToSchema, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "course_visibility", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CourseVisibility {
+    /// Aparece en el catálogo.
+    Public,
+    /// No aparece en el catálogo; se accede con el enlace (ID o slug).
+    Unlisted,
+    /// Como `unlisted`, pero solo se inscribe quien tiene un código de inscripción o
+    /// una invitación.
+    Private,
+}
+
+/// Cambios de estado permitidos.
+#[derive(Clone, Copy)]
+enum Transition {
+    SubmitForReview,
+    Publish,
+    Unpublish,
+    Archive,
+}
+
+impl Transition {
+    fn target(self) -> CourseStatus {
+        match self {
+            Transition::SubmitForReview => CourseStatus::Review,
+            Transition::Publish => CourseStatus::Published,
+            Transition::Unpublish => CourseStatus::Draft,
+            Transition::Archive => CourseStatus::Archived,
+        }
+    }
+
+    /// Estados desde los que se puede aplicar el cambio. Un administrador puede
+    /// publicar sin pasar por revisión.
+    fn allowed_from(self, from: CourseStatus, is_admin: bool) -> bool {
+        use CourseStatus::*;
+        match self {
+            Transition::SubmitForReview => from == Draft,
+            Transition::Publish => from == Review || (is_admin && from == Draft),
+            Transition::Unpublish => matches!(from, Review | Published | Archived),
+            Transition::Archive => matches!(from, Draft | Review | Published),
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            Transition::SubmitForReview => "enviar a revisión",
+            Transition::Publish => "publicar",
+            Transition::Unpublish => "devolver a borrador",
+            Transition::Archive => "archivar",
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/submit-review",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 200, description = "Curso enviado a revisión (`draft` → `review`)", body = Course),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El curso no está en borrador, o no tiene lecciones publicadas", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn submit_for_review(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    apply(&state, &claims, course_id, Transition::SubmitForReview).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/publish",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 200, description = "Curso publicado (`review` → `published`; un administrador también puede publicar desde `draft`)", body = Course),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El curso no está en revisión (o en borrador, si es un administrador), o no tiene lecciones publicadas", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn publish(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    apply(&state, &claims, course_id, Transition::Publish).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/unpublish",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 200, description = "Curso devuelto a borrador (`review`, `published` o `archived` → `draft`)", body = Course),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El curso ya está en borrador", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn unpublish(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    apply(&state, &claims, course_id, Transition::Unpublish).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/archive",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 200, description = "Curso archivado; deja de aparecer en el catálogo", body = Course),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El curso ya está archivado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn archive(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    apply(&state, &claims, course_id, Transition::Archive).await
+}
+
+/// Valida y aplica un cambio de estado con la fila del curso bloqueada.
+async fn apply(
+    state: &AppState,
+    claims: &Claims,
+    course_id: Uuid,
+    transition: Transition,
+) -> Result<Json<Course>, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    let current = sqlx::query!(
+        "SELECT instructor_id, course_status as \"course_status: CourseStatus\"
+         FROM courses WHERE id = $1 AND course_deleted_at IS NULL FOR UPDATE",
+        course_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+
+    if !can_manage_course(Some(claims), current.instructor_id) {
+        return Err(ApiError::Forbidden(
+            "Solo el instructor del curso o un administrador puede cambiar su estado".into(),
+        ));
+    }
+    if !transition.allowed_from(current.course_status, claims.role == Role::Admin) {
+        return Err(ApiError::Conflict(format!(
+            "No se puede {} un curso en estado `{}`",
+            transition.verb(),
+            current.course_status.as_str()
+        )));
+    }
+    if matches!(transition, Transition::SubmitForReview | Transition::Publish) {
+        ensure_has_published_lessons(&mut tx, course_id).await?;
+    }
+
+    // `course_published_at` guarda la primera publicación: volver a publicar no la cambia.
+    let course = sqlx::query_as!(
+        Course,
+        "UPDATE courses SET
+            course_status = $2,
+            course_published_at = CASE WHEN $2::course_status = 'published' THEN COALESCE(course_published_at, NOW()) ELSE course_published_at END,
+            course_updated_at = NOW()
+         WHERE id = $1
+         RETURNING id, instructor_id, course_name, course_slug, course_description,
+                   course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                   course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                   course_published_at, course_created_at",
+        course_id,
+        transition.target() as CourseStatus
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        "Curso {} pasó de `{}` a `{}` por {}",
+        course_id,
+        current.course_status.as_str(),
+        course.course_status.as_str(),
+        claims.sub
+    );
+    Ok(Json(course))
+}
+
+/// Un curso sin lecciones que los estudiantes puedan ver no se puede publicar.
+async fn ensure_has_published_lessons(conn: &mut PgConnection, course_id: Uuid) -> Result<(), ApiError> {
+    let has_lessons = sqlx::query_scalar!(
+        "SELECT EXISTS (
+            SELECT 1 FROM lessons l JOIN modules m ON m.id = l.module_id
+            WHERE m.course_id = $1
+              AND m.module_status = 'published' AND m.module_visibility = 'visible'
+              AND l.lesson_status = 'published' AND l.lesson_visibility = 'visible'
+         ) AS \"exists!\"",
+        course_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    if !has_lessons {
+        return Err(ApiError::Conflict(
+            "El curso necesita al menos una lección publicada dentro de un módulo publicado".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [CourseStatus; 4] = [
+        CourseStatus::Draft,
+        CourseStatus::Review,
+        CourseStatus::Published,
+        CourseStatus::Archived,
+    ];
+
+    fn allowed(transition: Transition, is_admin: bool) -> Vec<CourseStatus> {
+        ALL.into_iter().filter(|&from| transition.allowed_from(from, is_admin)).collect()
+    }
+
+    #[test]
+    fn instructor_transitions() {
+        use CourseStatus::*;
+        assert_eq!(allowed(Transition::SubmitForReview, false), [Draft]);
+        assert_eq!(allowed(Transition::Publish, false), [Review]);
+        assert_eq!(allowed(Transition::Unpublish, false), [Review, Published, Archived]);
+        assert_eq!(allowed(Transition::Archive, false), [Draft, Review, Published]);
+    }
+
+    #[test]
+    fn admin_can_publish_a_draft() {
+        use CourseStatus::*;
+        assert_eq!(allowed(Transition::Publish, true), [Draft, Review]);
+        assert_eq!(allowed(Transition::SubmitForReview, true), [Draft]);
+        assert_eq!(allowed(Transition::Unpublish, true), [Review, Published, Archived]);
+        assert_eq!(allowed(Transition::Archive, true), [Draft, Review, Published]);
+    }
+
+    #[test]
+    fn no_transition_keeps_the_status() {
+        for transition in [Transition::SubmitForReview, Transition::Publish, Transition::Unpublish, Transition::Archive] {
+            assert!(!transition.allowed_from(transition.target(), true));
+        }
+    }
+}
@@ -1,38 +1,44 @@
+use async_trait::async_trait;
 use axum::{
-    extract::{FromRequestParts, Path, State},
-    http::{request::Parts, StatusCode},
-    response::IntoResponse,
-    routing::{get, post, put},
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Redirect, Response},
+    routing::{delete, get, post, put},
     Json, Router,
 };
-use async_trait::async_trait;
-use axum_extra::headers::{authorization::Bearer, Authorization};
-use axum_extra::TypedHeader;
-use jsonwebtoken::{decode, DecodingKey, Validation};
-use dotenvy::dotenv;
-use sqlx::{postgres::PgPoolOptions, PgPool};
-use std::env;
+use jsonwebtoken::{decode, decode_header, Algorithm, Validation};
+use lms_common::auth::{roles::Instructor, AuthState};
+use lms_common::config::{self, env_or};
+use lms_common::error::{self, ApiError, ProblemDetails};
+use lms_common::mail::{self, MailSender};
+use lms_common::validation::{self, ValidatedJson};
+use lms_common::{Claims, RequireRole, Role};
+use sqlx::{postgres::PgPoolOptions, PgConnection, PgPool};
 use std::net::SocketAddr;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use std::sync::Arc;
+use std::time::Duration;
 use utoipa::{OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 use uuid::Uuid;
-
-// --- Estructuras de Autenticación (copiadas de identity-service) ---
-
-#[derive(Debug, serde::Serialize, serde::Deserialize, ToSchema, Clone, Copy, PartialEq)]
-pub enum Role {
-    Student,
-    Instructor,
-    Admin,
-}
-
-#[derive(Debug, serde::Deserialize)]
-struct Claims {
-    sub: Uuid,
-    role: Role,
-    exp: i64,
-}
+use validator::Validate;
+
+mod catalog;
+mod enrollments;
+mod invitations;
+mod jwks;
+mod lessons;
+mod lifecycle;
+mod modules;
+mod outline;
+mod revocation;
+mod search;
+mod slug;
+mod trash;
+
+use jwks::JwksCache;
+use lifecycle::{CourseStatus, CourseVisibility};
+use revocation::RevocationCache;
 
 // --- Estructuras de Datos y Schemas ---
 
@@ -41,26 +47,56 @@ struct Course {
     id: Uuid,
     instructor_id: Uuid,
     course_name: String,
+    /// Identificador legible y único del curso para las URLs.
+    #[schema(example = "introduccion-a-rust")]
+    course_slug: String,
     course_description: Option<String>,
+    course_status: CourseStatus,
+    course_visibility: CourseVisibility,
+    #[schema(example = "programacion")]
+    course_category: Option<String>,
+    /// Plazas del curso; `null` si no hay límite. Con el curso lleno, las nuevas
+    /// inscripciones pasan a la lista de espera.
+    max_enrollments: Option<i32>,
+    /// Desde cuándo se aceptan inscripciones; `null` si no hay fecha de apertura.
+    enrollment_opens_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Hasta cuándo se aceptan inscripciones; `null` si no hay fecha de cierre.
+    enrollment_closes_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Fecha de la primera publicación; no cambia si el curso se despublica y se
+    /// vuelve a publicar.
+    course_published_at: Option<chrono::DateTime<chrono::Utc>>,
     course_created_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// Payload para crear un nuevo curso.
-#[derive(serde::Deserialize, ToSchema)]
+#[derive(serde::Deserialize, ToSchema, Validate)]
 struct CreateCourse {
-    #[schema(example = "Introducción a Rust")]
+    #[schema(example = "Introducción a Rust", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
     course_name: String,
-    #[schema(example = "Un curso para principiantes sobre el lenguaje de programación Rust.")]
+    #[schema(example = "Un curso para principiantes sobre el lenguaje de programación Rust.", max_length = 10000)]
+    #[validate(length(max = 10000, message = "Debe tener como máximo 10000 caracteres"))]
     course_description: Option<String>,
+    /// Si aparece en el catálogo (por defecto `public`). El curso se crea en borrador.
+    course_visibility: Option<CourseVisibility>,
+    #[schema(example = "programacion", max_length = 100)]
+    #[validate(length(min = 1, max = 100, message = "Debe tener entre 1 y 100 caracteres"), custom(function = "validation::not_blank"))]
+    course_category: Option<String>,
 }
 
-/// Payload para actualizar un curso.
-#[derive(serde::Deserialize, ToSchema)]
+/// Payload para actualizar un curso. El estado se cambia con los endpoints de publicación.
+#[derive(serde::Deserialize, ToSchema, Validate)]
 struct UpdateCourse {
-    #[schema(example = "Introducción a Rust Avanzado")]
+    #[schema(example = "Introducción a Rust Avanzado", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
     course_name: Option<String>,
-    #[schema(example = "Un curso avanzado sobre Rust.")]
+    #[schema(example = "Un curso avanzado sobre Rust.", max_length = 10000)]
+    #[validate(length(max = 10000, message = "Debe tener como máximo 10000 caracteres"))]
     course_description: Option<String>,
+    course_visibility: Option<CourseVisibility>,
+    #[schema(example = "programacion", max_length = 100)]
+    #[validate(length(min = 1, max = 100, message = "Debe tener entre 1 y 100 caracteres"), custom(function = "validation::not_blank"))]
+    course_category: Option<String>,
 }
 
 // --- Estado de la Aplicación ---
@@ -68,7 +104,17 @@ struct UpdateCourse {
 #[derive(Clone)]
 struct AppState {
     db_pool: PgPool,
-    jwt_secret: String,
+    jwks: Arc<JwksCache>,
+    revocations: Arc<RevocationCache>,
+    /// Días que un curso eliminado puede restaurarse antes de purgarse.
+    course_retention_days: i32,
+    /// Inscribirse exige el email verificado (`email_verified` en el token).
+    require_verified_email: bool,
+    mailer: Arc<dyn MailSender>,
+    /// URL del frontend, para los enlaces de las invitaciones.
+    app_base_url: String,
+    /// Días de validez de una invitación por email.
+    invitation_ttl_days: i64,
 }
 
 // --- Documentación de la API (OpenAPI) ---
@@ -77,13 +123,67 @@ struct AppState {
 #[openapi(
     paths(
         health_check,
-        create_course
+        create_course,
+        catalog::list_courses,
+        search::search_courses,
+        get_course,
+        get_course_by_slug,
+        update_course,
+        trash::delete_course,
+        trash::restore_course,
+        trash::list_deleted_courses,
+        trash::purge_deleted_courses,
+        lifecycle::submit_for_review,
+        lifecycle::publish,
+        lifecycle::unpublish,
+        lifecycle::archive,
+        enrollments::enroll,
+        enrollments::unenroll,
+        enrollments::list_my_courses,
+        enrollments::list_course_enrollments,
+        enrollments::update_enrollment,
+        enrollments::update_enrollment_settings,
+        invitations::create_enrollment_code,
+        invitations::list_enrollment_codes,
+        invitations::revoke_enrollment_code,
+        invitations::redeem_enrollment_code,
+        invitations::create_invitations,
+        invitations::list_invitations,
+        invitations::revoke_invitation,
+        invitations::accept_invitation,
+        invitations::import_roster,
+        modules::create_module,
+        modules::list_modules,
+        modules::get_module,
+        modules::update_module,
+        modules::delete_module,
+        modules::reorder_modules,
+        lessons::create_lesson,
+        lessons::list_lessons,
+        lessons::get_lesson,
+        lessons::update_lesson,
+        lessons::delete_lesson,
+        lessons::reorder_lessons,
+        outline::get_outline,
+        lessons::list_content_versions
     ),
     components(
-        schemas(Course, CreateCourse, Role)
+        schemas(Course, CreateCourse, UpdateCourse, Role,
+            lifecycle::CourseStatus, lifecycle::CourseVisibility, trash::DeletedCourse, trash::PurgeResult,
+            catalog::CoursePage, catalog::CourseSort, search::CourseSearchPage, search::CourseSearchHit, ProblemDetails, validation::FieldError,
+            enrollments::Enrollment, enrollments::EnrollmentStatus, enrollments::EnrolledCourse, enrollments::UpdateEnrollment,
+            enrollments::EnrollmentSettings,
+            invitations::EnrollmentCode, invitations::CreateEnrollmentCode, invitations::RedeemEnrollmentCode,
+            invitations::CourseInvitation, invitations::CreateInvitations, invitations::AcceptInvitation,
+            invitations::RosterImport, invitations::RosterRowError,
+            modules::Module, modules::CreateModule, modules::UpdateModule, modules::ReorderModules,
+            modules::PublishStatus, modules::ItemVisibility,
+            lessons::Lesson, lessons::LessonSummary, lessons::LessonContent, lessons::LessonContentVersion,
+            lessons::CreateLesson, lessons::UpdateLesson, lessons::ReorderLessons,
+            outline::CourseOutline, outline::OutlineModule, outline::OutlineLesson)
     ),
     tags(
-        (name = "Course Service", description = "API para gestión de cursos y módulos")
+        (name = "Course Service", description = "API para gestión de cursos, módulos y lecciones")
     )
 )]
 struct ApiDoc;
@@ -92,33 +192,86 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() {
-    eprintln!("Starting course service");
-    dotenv().ok();
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL debe estar configurada");
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET debe estar configurado");
+    config::init("course-service");
+
+    let database_url = config::required("DATABASE_URL");
+    let identity_service_url = config::required("IDENTITY_SERVICE_URL");
+    let jwks_refresh_interval: u64 = env_or("JWKS_REFRESH_SECONDS", 300);
+    let revocation_cache_ttl: u64 = env_or("REVOCATION_CACHE_TTL_SECONDS", 30);
+    let course_retention_days: i32 = env_or("COURSE_RETENTION_DAYS", 30);
+    // La misma política que en identity-service: salvo `none`, inscribirse exige email verificado
+    let require_verified_email = env_or("EMAIL_VERIFICATION_POLICY", "enrollment".to_string()) != "none";
+    let app_base_url = env_or("APP_BASE_URL", "http://localhost:8080".to_string());
+    let invitation_ttl_days: i64 = env_or("INVITATION_TTL_DAYS", 14);
 
     let db_pool = PgPoolOptions::new()
         .max_connections(5)
         .connect(&database_url)
         .await
         .expect("No se pudo conectar a la base de datos");
-    
-    let app_state = AppState { db_pool, jwt_secret };
+
+    let jwks = Arc::new(JwksCache::new(&identity_service_url));
+    jwks.clone().spawn_refresh_task(Duration::from_secs(jwks_refresh_interval));
+
+    let app_state = AppState {
+        db_pool,
+        jwks,
+        revocations: Arc::new(RevocationCache::new(Duration::from_secs(revocation_cache_ttl))),
+        course_retention_days,
+        require_verified_email,
+        mailer: mail::from_env(),
+        app_base_url,
+        invitation_ttl_days,
+    };
+    trash::spawn_purge_task(app_state.db_pool.clone(), course_retention_days, Duration::from_secs(3600));
 
     let app = Router::new()
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .route("/health", get(health_check))
         .route("/api/v1/courses", post(create_course))
-        .route("/api/v1/courses", get(list_courses))
+        .route("/api/v1/courses", get(catalog::list_courses))
+        .route("/api/v1/courses/search", get(search::search_courses))
         .route("/api/v1/courses/{id}", get(get_course))
+        .route("/api/v1/courses/by-slug/{slug}", get(get_course_by_slug))
         .route("/api/v1/courses/{id}", put(update_course))
+        .route("/api/v1/courses/{id}", delete(trash::delete_course))
+        .route("/api/v1/courses/{id}/restore", post(trash::restore_course))
+        .route("/api/v1/courses/trash", get(trash::list_deleted_courses))
+        .route("/api/v1/admin/courses/purge", post(trash::purge_deleted_courses))
+        .route("/api/v1/courses/{id}/submit-review", post(lifecycle::submit_for_review))
+        .route("/api/v1/courses/{id}/publish", post(lifecycle::publish))
+        .route("/api/v1/courses/{id}/unpublish", post(lifecycle::unpublish))
+        .route("/api/v1/courses/{id}/archive", post(lifecycle::archive))
+        .route("/api/v1/courses/{id}/outline", get(outline::get_outline))
+        .route("/api/v1/courses/{id}/enrollment", post(enrollments::enroll))
+        .route("/api/v1/courses/{id}/enrollment", delete(enrollments::unenroll))
+        .route("/api/v1/courses/{id}/enrollments", get(enrollments::list_course_enrollments))
+        .route("/api/v1/courses/{id}/enrollments/{student_id}", put(enrollments::update_enrollment))
+        .route("/api/v1/courses/{id}/enrollment-settings", put(enrollments::update_enrollment_settings))
+        .route("/api/v1/me/courses", get(enrollments::list_my_courses))
+        .route("/api/v1/courses/{id}/enrollment-codes", post(invitations::create_enrollment_code))
+        .route("/api/v1/courses/{id}/enrollment-codes", get(invitations::list_enrollment_codes))
+        .route("/api/v1/courses/{id}/enrollment-codes/{code_id}", delete(invitations::revoke_enrollment_code))
+        .route("/api/v1/enrollment-codes/redeem", post(invitations::redeem_enrollment_code))
+        .route("/api/v1/courses/{id}/invitations", post(invitations::create_invitations))
+        .route("/api/v1/courses/{id}/invitations", get(invitations::list_invitations))
+        .route("/api/v1/courses/{id}/invitations/{invitation_id}", delete(invitations::revoke_invitation))
+        .route("/api/v1/invitations/accept", post(invitations::accept_invitation))
+        .route("/api/v1/courses/{id}/roster", post(invitations::import_roster))
+        .route("/api/v1/courses/{id}/modules", post(modules::create_module))
+        .route("/api/v1/courses/{id}/modules", get(modules::list_modules))
+        .route("/api/v1/courses/{id}/modules/{module_id}", get(modules::get_module))
+        .route("/api/v1/courses/{id}/modules/{module_id}", put(modules::update_module))
+        .route("/api/v1/courses/{id}/modules/{module_id}", delete(modules::delete_module))
+        .route("/api/v1/courses/{id}/modules/order", put(modules::reorder_modules))
+        .route("/api/v1/modules/{id}/lessons", post(lessons::create_lesson))
+        .route("/api/v1/modules/{id}/lessons", get(lessons::list_lessons))
+        .route("/api/v1/modules/{id}/lessons/{lesson_id}", get(lessons::get_lesson))
+        .route("/api/v1/modules/{id}/lessons/{lesson_id}", put(lessons::update_lesson))
+        .route("/api/v1/modules/{id}/lessons/{lesson_id}", delete(lessons::delete_lesson))
+        .route("/api/v1/modules/{id}/lessons/order", put(lessons::reorder_lessons))
+        .route("/api/v1/modules/{id}/lessons/{lesson_id}/versions", get(lessons::list_content_versions))
+        .layer(middleware::from_fn(error::problem_details))
         .with_state(app_state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 3001));
@@ -145,9 +298,10 @@ async fn health_check() -> &'static str {
     request_body = CreateCourse,
     responses(
         (status = 201, description = "Curso creado exitosamente", body = Course),
-        (status = 401, description = "No autorizado (token inválido o ausente)"),
-        (status = 403, description = "Prohibido (el usuario no es instructor)"),
-        (status = 500, description = "Error interno del servidor")
+        (status = 401, description = "No autorizado (token inválido o ausente)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es instructor)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
     ),
     security(
         ("bearer_auth" = [])
@@ -155,90 +309,128 @@ async fn health_check() -> &'static str {
 )]
 async fn create_course(
     State(state): State<AppState>,
-    claims: Claims, // El extractor se encargará de la validación del token
-    Json(payload): Json<CreateCourse>,
-) -> impl IntoResponse {
-    // Autorización: Solo los instructores pueden crear cursos.
-    if claims.role != Role::Instructor {
-        return (StatusCode::FORBIDDEN, "Solo los instructores pueden crear cursos").into_response();
-    }
+    instructor: RequireRole<Instructor>, // Solo los instructores pueden crear cursos
+    ValidatedJson(payload): ValidatedJson<CreateCourse>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+    let slug = slug::unique_course_slug(&mut tx, &payload.course_name, None).await?;
 
-    let new_course_result = sqlx::query_as!(
+    let course = sqlx::query_as!(
         Course,
-        "INSERT INTO courses (instructor_id, course_name, course_description) VALUES ($1, $2, $3) RETURNING id, instructor_id, course_name, course_description, course_created_at",
-        claims.sub, // El ID del instructor viene del token JWT
-        payload.course_name,
-        payload.course_description
+        "INSERT INTO courses (instructor_id, course_name, course_slug, course_description, course_visibility, course_category)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, instructor_id, course_name, course_slug, course_description,
+                   course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                   course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                   course_published_at, course_created_at",
+        instructor.sub, // El ID del instructor viene del token JWT
+        payload.course_name.trim(),
+        slug,
+        payload.course_description,
+        payload.course_visibility.unwrap_or(CourseVisibility::Public) as CourseVisibility,
+        payload.course_category.as_deref().map(str::trim)
     )
-    .fetch_one(&state.db_pool)
-    .await;
-
-    match new_course_result {
-        Ok(course) => (StatusCode::CREATED, Json(course)).into_response(),
-        Err(e) => {
-            tracing::error!("Error al crear el curso: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        }
-    }
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(course)))
 }
 
 #[utoipa::path(
     get,
-    path = "/api/v1/courses",
+    path = "/api/v1/courses/{id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
     responses(
-        (status = 200, description = "Lista de cursos obtenida exitosamente", body = Vec<Course>),
-        (status = 500, description = "Error interno del servidor")
+        (status = 200, description = "Curso obtenido exitosamente", body = Course),
+        (status = 401, description = "Token inválido", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado (o en borrador o revisión, para quien no lo administra)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        (),
+        ("bearer_auth" = [])
     )
 )]
-async fn list_courses(State(state): State<AppState>) -> impl IntoResponse {
-    let courses_result = sqlx::query_as!(
+async fn get_course(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let course = sqlx::query_as!(
         Course,
-        "SELECT id, instructor_id, course_name, course_description, course_created_at FROM courses"
+        "SELECT id, instructor_id, course_name, course_slug, course_description,
+                course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                course_published_at, course_created_at
+         FROM courses WHERE id = $1 AND course_deleted_at IS NULL",
+        id
     )
-    .fetch_all(&state.db_pool)
-    .await;
-
-    match courses_result {
-        Ok(courses) => (StatusCode::OK, Json(courses)).into_response(),
-        Err(e) => {
-            tracing::error!("Error al obtener cursos: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        }
-    }
+    .fetch_optional(&state.db_pool)
+    .await?
+    .filter(|course| course.is_visible_to(claims.as_ref()))
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+
+    Ok(Json(course))
 }
 
 #[utoipa::path(
     get,
-    path = "/api/v1/courses/{id}",
+    path = "/api/v1/courses/by-slug/{slug}",
     params(
-        ("id" = Uuid, Path, description = "ID del curso")
+        ("slug" = String, Path, description = "Slug actual o anterior del curso")
     ),
     responses(
         (status = 200, description = "Curso obtenido exitosamente", body = Course),
-        (status = 404, description = "Curso no encontrado"),
-        (status = 500, description = "Error interno del servidor")
+        (status = 308, description = "Slug anterior de un curso renombrado; `Location` apunta al slug actual"),
+        (status = 401, description = "Token inválido", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado (o en borrador o revisión, para quien no lo administra)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        (),
+        ("bearer_auth" = [])
     )
 )]
-async fn get_course(
+async fn get_course_by_slug(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    let course_result = sqlx::query_as!(
+    claims: Option<Claims>,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    let course = sqlx::query_as!(
         Course,
-        "SELECT id, instructor_id, course_name, course_description, course_created_at FROM courses WHERE id = $1",
-        id
+        "SELECT id, instructor_id, course_name, course_slug, course_description,
+                course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                course_published_at, course_created_at
+         FROM courses WHERE course_slug = $1 AND course_deleted_at IS NULL",
+        slug
     )
     .fetch_optional(&state.db_pool)
-    .await;
-
-    match course_result {
-        Ok(Some(course)) => (StatusCode::OK, Json(course)).into_response(),
-        Ok(None) => StatusCode::NOT_FOUND.into_response(),
-        Err(e) => {
-            tracing::error!("Error al obtener curso: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    .await?;
+    if let Some(course) = course {
+        if !course.is_visible_to(claims.as_ref()) {
+            return Err(ApiError::NotFound("El curso no existe".into()));
         }
+        return Ok(Json(course).into_response());
     }
+
+    // Enlaces viejos: redirigir al slug vigente, con la misma regla de visibilidad
+    // para no revelar el slug nuevo de un curso que el usuario no puede ver
+    let current = sqlx::query!(
+        "SELECT c.course_slug, c.instructor_id, c.course_status as \"course_status: CourseStatus\"
+         FROM course_slug_redirects r JOIN courses c ON c.id = r.course_id
+         WHERE r.old_slug = $1 AND c.course_deleted_at IS NULL",
+        slug
+    )
+    .fetch_optional(&state.db_pool)
+    .await?
+    .filter(|c| course_visible_to(claims.as_ref(), c.course_status, c.instructor_id))
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+
+    Ok(Redirect::permanent(&format!("/api/v1/courses/by-slug/{}", current.course_slug)).into_response())
 }
 
 #[utoipa::path(
@@ -250,10 +442,11 @@ async fn get_course(
     request_body = UpdateCourse,
     responses(
         (status = 200, description = "Curso actualizado exitosamente", body = Course),
-        (status = 401, description = "No autorizado"),
-        (status = 403, description = "Prohibido (no es el instructor)"),
-        (status = 404, description = "Curso no encontrado"),
-        (status = 500, description = "Error interno del servidor")
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
     ),
     security(
         ("bearer_auth" = [])
@@ -263,77 +456,176 @@ async fn update_course(
     State(state): State<AppState>,
     claims: Claims,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateCourse>,
-) -> impl IntoResponse {
-    // Verificar que el curso existe y obtener el instructor_id
-    let course_check = sqlx::query!(
-        "SELECT instructor_id FROM courses WHERE id = $1",
-        id
+    ValidatedJson(payload): ValidatedJson<UpdateCourse>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+    // Verificar que el curso existe y que pertenece al instructor
+    lock_course_for_instructor(&mut tx, id, &claims).await?;
+
+    // Al renombrar se regenera el slug y el anterior queda como redirección
+    let new_slug = match &payload.course_name {
+        Some(name) => Some(slug::unique_course_slug(&mut tx, name, Some(id)).await?),
+        None => None,
+    };
+    let old_slug = sqlx::query_scalar!("SELECT course_slug FROM courses WHERE id = $1", id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    // Actualizar solo los campos proporcionados
+    let updated_course = sqlx::query_as!(
+        Course,
+        "UPDATE courses SET
+            course_name = COALESCE($2, course_name),
+            course_slug = COALESCE($3, course_slug),
+            course_description = COALESCE($4, course_description),
+            course_visibility = COALESCE($5, course_visibility),
+            course_category = COALESCE($6, course_category),
+            course_updated_at = NOW()
+        WHERE id = $1
+        RETURNING id, instructor_id, course_name, course_slug, course_description,
+                   course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                   course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                   course_published_at, course_created_at",
+        id,
+        payload.course_name.as_deref().map(str::trim),
+        new_slug,
+        payload.course_description,
+        payload.course_visibility as Option<CourseVisibility>,
+        payload.course_category.as_deref().map(str::trim)
     )
-    .fetch_optional(&state.db_pool)
-    .await;
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if updated_course.course_slug != old_slug {
+        sqlx::query!(
+            "INSERT INTO course_slug_redirects (old_slug, course_id) VALUES ($1, $2)
+             ON CONFLICT (old_slug) DO UPDATE SET course_id = EXCLUDED.course_id, created_at = NOW()",
+            old_slug,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+        // Si el curso recupera un slug anterior, deja de ser una redirección
+        sqlx::query!("DELETE FROM course_slug_redirects WHERE old_slug = $1", updated_course.course_slug)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
 
-    match course_check {
-        Ok(Some(course)) => {
-            if course.instructor_id != claims.sub {
-                return StatusCode::FORBIDDEN.into_response();
-            }
+    Ok(Json(updated_course))
+}
 
-            // Actualizar solo los campos proporcionados
-            let update_result = sqlx::query_as!(
-                Course,
-                "UPDATE courses SET 
-                    course_name = COALESCE($2, course_name),
-                    course_description = COALESCE($3, course_description)
-                WHERE id = $1
-                RETURNING id, instructor_id, course_name, course_description, course_created_at",
-                id,
-                payload.course_name,
-                payload.course_description
-            )
-            .fetch_one(&state.db_pool)
-            .await;
-
-            match update_result {
-                Ok(updated_course) => (StatusCode::OK, Json(updated_course)).into_response(),
-                Err(e) => {
-                    tracing::error!("Error al actualizar curso: {:?}", e);
-                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
-                }
-            }
-        }
-        Ok(None) => StatusCode::NOT_FOUND.into_response(),
-        Err(e) => {
-            tracing::error!("Error al verificar curso: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        }
+impl Course {
+    /// Los cursos en borrador o revisión solo los ven su instructor y los administradores.
+    fn is_visible_to(&self, claims: Option<&Claims>) -> bool {
+        course_visible_to(claims, self.course_status, self.instructor_id)
+    }
+}
+
+/// Regla de `Course::is_visible_to` para cuando solo se tiene el estado y el instructor.
+fn course_visible_to(claims: Option<&Claims>, status: CourseStatus, instructor_id: Uuid) -> bool {
+    status.is_visible_to_students() || can_manage_course(claims, instructor_id)
+}
+
+/// El instructor del curso y los administradores lo administran: ven sus borradores
+/// y elementos ocultos, y cambian su estado.
+fn can_manage_course(claims: Option<&Claims>, instructor_id: Uuid) -> bool {
+    claims.is_some_and(|c| c.role == Role::Admin || c.sub == instructor_id)
+}
+
+/// Indica si el usuario administra el curso. Responde 404 si el curso no existe o si
+/// el usuario no lo administra y el curso no está publicado ni archivado.
+async fn course_access(db_pool: &PgPool, course_id: Uuid, claims: Option<&Claims>) -> Result<bool, ApiError> {
+    let course = sqlx::query!(
+        "SELECT instructor_id, course_status as \"course_status: CourseStatus\"
+         FROM courses WHERE id = $1 AND course_deleted_at IS NULL",
+        course_id
+    )
+    .fetch_optional(db_pool)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+
+    let can_manage = can_manage_course(claims, course.instructor_id);
+    if !can_manage && !course.course_status.is_visible_to_students() {
+        return Err(ApiError::NotFound("El curso no existe".into()));
     }
+    Ok(can_manage)
+}
+
+/// Devuelve el instructor del curso, o 404 si el curso no existe.
+async fn course_instructor(db_pool: &PgPool, course_id: Uuid) -> Result<Uuid, ApiError> {
+    sqlx::query_scalar!(
+        "SELECT instructor_id FROM courses WHERE id = $1 AND course_deleted_at IS NULL",
+        course_id
+    )
+    .fetch_optional(db_pool)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))
 }
 
-// --- Extractor de Claims JWT ---
+/// Comprueba que el curso existe (404) y que el usuario es su instructor (403).
+async fn ensure_course_instructor(db_pool: &PgPool, course_id: Uuid, claims: &Claims) -> Result<(), ApiError> {
+    if course_instructor(db_pool, course_id).await? != claims.sub {
+        return Err(not_course_instructor());
+    }
+    Ok(())
+}
 
-impl FromRequestParts<AppState> for Claims {
-    type Rejection = StatusCode;
+/// Como `ensure_course_instructor`, pero además bloquea la fila del curso hasta el
+/// final de la transacción. Las altas, bajas y reordenamientos de módulos y lecciones
+/// de un mismo curso se serializan con este bloqueo, así no compiten por las posiciones.
+async fn lock_course_for_instructor(
+    conn: &mut PgConnection,
+    course_id: Uuid,
+    claims: &Claims,
+) -> Result<(), ApiError> {
+    let instructor_id = sqlx::query_scalar!(
+        "SELECT instructor_id FROM courses WHERE id = $1 AND course_deleted_at IS NULL FOR UPDATE",
+        course_id
+    )
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
 
-    async fn from_request_parts(
-        parts: &mut Parts,
-        state: &AppState,
-    ) -> Result<Self, Self::Rejection> {
-        // Extraer el token de la cabecera `Authorization: Bearer <token>`
-        let authorization = parts.headers
-            .get("authorization")
-            .and_then(|h| h.to_str().ok())
-            .and_then(|h| h.strip_prefix("Bearer "))
+    if instructor_id != claims.sub {
+        return Err(not_course_instructor());
+    }
+    Ok(())
+}
+
+fn not_course_instructor() -> ApiError {
+    ApiError::Forbidden("Solo el instructor del curso puede modificarlo".into())
+}
+
+// --- Verificación de access tokens ---
+
+#[async_trait]
+impl AuthState for AppState {
+    async fn verify_access_token(&self, token: &str) -> Result<Claims, StatusCode> {
+        // Buscar la clave pública indicada por el `kid` del token
+        let kid = decode_header(token)
+            .ok()
+            .and_then(|header| header.kid)
             .ok_or(StatusCode::UNAUTHORIZED)?;
+        let key = self.jwks.decoding_key(&kid).await.ok_or(StatusCode::UNAUTHORIZED)?;
 
         // Decodificar y validar el token
-        let token_data = decode::<Claims>(
-            authorization,
-            &DecodingKey::from_secret(state.jwt_secret.as_ref()),
-            &Validation::default(),
-        )
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-        Ok(token_data.claims)
+        let claims = decode::<Claims>(token, &key, &Validation::new(Algorithm::EdDSA))
+            .map_err(|_| StatusCode::UNAUTHORIZED)?
+            .claims;
+
+        // Rechazar tokens revocados por identity-service
+        match self
+            .revocations
+            .is_revoked(&self.db_pool, claims.jti, claims.sub, claims.issued_at())
+            .await
+        {
+            Ok(false) => Ok(claims),
+            Ok(true) => Err(StatusCode::UNAUTHORIZED),
+            Err(e) => {
+                tracing::error!("Error al consultar revocaciones: {:?}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
     }
 }
@@ -0,0 +1,459 @@
+// --- Módulos de un curso ---
+//
+// Rutas anidadas en `/api/v1/courses/{id}/modules`. Solo el instructor del curso
+// crea, modifica o elimina módulos; el resto de usuarios ve únicamente los
+// módulos publicados y visibles.
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use lms_common::auth::roles::Instructor;
+use lms_common::validation::{self, ValidatedJson};
+use lms_common::{ApiError, Claims, ProblemDetails, RequireRole};
+
+use crate::slug::slugify;
+use crate::{
+    can_manage_course, course_access, ensure_course_instructor, lock_course_for_instructor, not_course_instructor, AppState,
+};
+
+/// Estado de publicación de un módulo o una lección. En la base de datos es el tipo `publish_status`.
+#[derive(Debug, Serialize, Deserialize, ToSchema, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "publish_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PublishStatus {
+    Draft,
+    Published,
+}
+
+/// Visibilidad de un módulo o una lección. En la base de datos es el tipo `item_visibility`.
+#[derive(Debug, Serialize, Deserialize, ToSchema, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "item_visibility", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ItemVisibility {
+    Visible,
+    Hidden,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Module {
+    id: Uuid,
+    course_id: Uuid,
+    module_name: String,
+    #[schema(example = "primeros-pasos")]
+    module_slug: String,
+    module_description: Option<String>,
+    /// Posición del módulo dentro del curso (ascendente).
+    module_order: i32,
+    module_status: PublishStatus,
+    module_visibility: ItemVisibility,
+    module_created_at: Option<DateTime<Utc>>,
+    module_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Payload para crear un módulo. Se agrega al final del curso.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct CreateModule {
+    #[schema(example = "Primeros pasos", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
+    module_name: String,
+    #[schema(max_length = 10000)]
+    #[validate(length(max = 10000, message = "Debe tener como máximo 10000 caracteres"))]
+    module_description: Option<String>,
+    /// Por defecto `draft`.
+    module_status: Option<PublishStatus>,
+    /// Por defecto `visible`.
+    module_visibility: Option<ItemVisibility>,
+}
+
+/// Payload para actualizar un módulo. Solo se modifican los campos enviados.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct UpdateModule {
+    #[schema(example = "Primeros pasos con Rust", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
+    module_name: Option<String>,
+    #[schema(max_length = 10000)]
+    #[validate(length(max = 10000, message = "Debe tener como máximo 10000 caracteres"))]
+    module_description: Option<String>,
+    module_status: Option<PublishStatus>,
+    module_visibility: Option<ItemVisibility>,
+}
+
+/// Payload para reordenar los módulos de un curso.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct ReorderModules {
+    /// Todos los módulos del curso, en el orden deseado.
+    #[validate(custom(function = "validation::unique_ids"))]
+    module_ids: Vec<Uuid>,
+}
+
+/// Indica si el usuario puede ver los módulos en borrador u ocultos del curso:
+/// solo su instructor y los administradores. Responde 404 si el curso no existe
+/// o si todavía no está publicado y el usuario no lo administra.
+async fn can_see_unpublished(
+    state: &AppState,
+    course_id: Uuid,
+    claims: Option<&Claims>,
+) -> Result<bool, ApiError> {
+    course_access(&state.db_pool, course_id, claims).await
+}
+
+/// Datos de un módulo necesarios para autorizar el acceso a sus lecciones.
+pub struct ModuleAccess {
+    pub course_id: Uuid,
+    pub instructor_id: Uuid,
+    /// El módulo está publicado y visible, y su curso publicado o archivado.
+    pub is_public: bool,
+}
+
+impl ModuleAccess {
+    /// Busca el módulo y el instructor de su curso. Responde 404 si no existe.
+    pub async fn load(db_pool: &PgPool, module_id: Uuid) -> Result<Self, ApiError> {
+        sqlx::query_as!(
+            ModuleAccess,
+            "SELECT m.course_id, c.instructor_id,
+                    (m.module_status = 'published' AND m.module_visibility = 'visible'
+                     AND c.course_status IN ('published', 'archived')) AS \"is_public!\"
+             FROM modules m JOIN courses c ON c.id = m.course_id
+             WHERE m.id = $1 AND c.course_deleted_at IS NULL",
+            module_id
+        )
+        .fetch_optional(db_pool)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("El módulo no existe".into()))
+    }
+
+    /// El instructor del curso y los administradores ven borradores y elementos ocultos.
+    pub fn can_see_unpublished(&self, claims: Option<&Claims>) -> bool {
+        can_manage_course(claims, self.instructor_id)
+    }
+
+    /// Solo el instructor del curso puede modificar el módulo y sus lecciones.
+    pub fn ensure_instructor(&self, claims: &Claims) -> Result<(), ApiError> {
+        if claims.sub != self.instructor_id {
+            return Err(not_course_instructor());
+        }
+        Ok(())
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/modules",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    request_body = CreateModule,
+    responses(
+        (status = 201, description = "Módulo creado al final del curso", body = Module),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_module(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path(course_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateModule>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+    lock_course_for_instructor(&mut tx, course_id, &instructor).await?;
+
+    let module = sqlx::query_as!(
+        Module,
+        "INSERT INTO modules (course_id, module_name, module_slug, module_description, module_order, module_status, module_visibility)
+         VALUES ($1, $2, $3, $4,
+                 (SELECT COALESCE(MAX(module_order), 0) + 1 FROM modules WHERE course_id = $1),
+                 $5, $6)
+         RETURNING id, course_id, module_name, module_slug, module_description, module_order,
+                   module_status as \"module_status: _\", module_visibility as \"module_visibility: _\",
+                   module_created_at, module_updated_at",
+        course_id,
+        payload.module_name.trim(),
+        slugify(&payload.module_name),
+        payload.module_description,
+        payload.module_status.unwrap_or(PublishStatus::Draft) as PublishStatus,
+        payload.module_visibility.unwrap_or(ItemVisibility::Visible) as ItemVisibility
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(module)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses/{id}/modules",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 200, description = "Módulos del curso ordenados por `module_order`. Los borradores y ocultos solo los ve el instructor del curso o un administrador", body = Vec<Module>),
+        (status = 401, description = "Token inválido", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        (),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_modules(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let show_all = can_see_unpublished(&state, course_id, claims.as_ref()).await?;
+
+    let modules = sqlx::query_as!(
+        Module,
+        "SELECT id, course_id, module_name, module_slug, module_description, module_order,
+                module_status as \"module_status: _\", module_visibility as \"module_visibility: _\",
+                module_created_at, module_updated_at
+         FROM modules
+         WHERE course_id = $1
+           AND ($2 OR (module_status = 'published' AND module_visibility = 'visible'))
+         ORDER BY module_order, module_created_at",
+        course_id,
+        show_all
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(modules))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses/{id}/modules/{module_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso"),
+        ("module_id" = Uuid, Path, description = "ID del módulo")
+    ),
+    responses(
+        (status = 200, description = "Módulo obtenido exitosamente", body = Module),
+        (status = 401, description = "Token inválido", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso o módulo no encontrado (o no publicado)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        (),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_module(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path((course_id, module_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let show_all = can_see_unpublished(&state, course_id, claims.as_ref()).await?;
+
+    let module = sqlx::query_as!(
+        Module,
+        "SELECT id, course_id, module_name, module_slug, module_description, module_order,
+                module_status as \"module_status: _\", module_visibility as \"module_visibility: _\",
+                module_created_at, module_updated_at
+         FROM modules
+         WHERE id = $1 AND course_id = $2
+           AND ($3 OR (module_status = 'published' AND module_visibility = 'visible'))",
+        module_id,
+        course_id,
+        show_all
+    )
+    .fetch_optional(&state.db_pool)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El módulo no existe".into()))?;
+
+    Ok(Json(module))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/courses/{id}/modules/{module_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso"),
+        ("module_id" = Uuid, Path, description = "ID del módulo")
+    ),
+    request_body = UpdateModule,
+    responses(
+        (status = 200, description = "Módulo actualizado. Si cambia el nombre se regenera el slug", body = Module),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso o módulo no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_module(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path((course_id, module_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<UpdateModule>,
+) -> Result<impl IntoResponse, ApiError> {
+    ensure_course_instructor(&state.db_pool, course_id, &instructor).await?;
+
+    let module_name = payload.module_name.as_deref().map(str::trim);
+    let module = sqlx::query_as!(
+        Module,
+        "UPDATE modules SET
+            module_name = COALESCE($3, module_name),
+            module_slug = COALESCE($4, module_slug),
+            module_description = COALESCE($5, module_description),
+            module_status = COALESCE($6, module_status),
+            module_visibility = COALESCE($7, module_visibility),
+            module_updated_at = NOW()
+         WHERE id = $1 AND course_id = $2
+         RETURNING id, course_id, module_name, module_slug, module_description, module_order,
+                   module_status as \"module_status: _\", module_visibility as \"module_visibility: _\",
+                   module_created_at, module_updated_at",
+        module_id,
+        course_id,
+        module_name,
+        module_name.map(slugify),
+        payload.module_description,
+        payload.module_status as Option<PublishStatus>,
+        payload.module_visibility as Option<ItemVisibility>
+    )
+    .fetch_optional(&state.db_pool)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El módulo no existe".into()))?;
+
+    Ok(Json(module))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/courses/{id}/modules/{module_id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso"),
+        ("module_id" = Uuid, Path, description = "ID del módulo")
+    ),
+    responses(
+        (status = 204, description = "Módulo eliminado junto con sus lecciones. Los módulos siguientes suben una posición"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso o módulo no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_module(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path((course_id, module_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+    lock_course_for_instructor(&mut tx, course_id, &instructor).await?;
+
+    let deleted_order = sqlx::query_scalar!(
+        "DELETE FROM modules WHERE id = $1 AND course_id = $2 RETURNING module_order",
+        module_id,
+        course_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El módulo no existe".into()))?;
+
+    // Cerrar el hueco que deja el módulo
+    sqlx::query!(
+        "UPDATE modules SET module_order = module_order - 1 WHERE course_id = $1 AND module_order > $2",
+        course_id,
+        deleted_order
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/courses/{id}/modules/order",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    request_body = ReorderModules,
+    responses(
+        (status = 200, description = "Módulos del curso en el nuevo orden", body = Vec<Module>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "La lista no coincide con los módulos actuales del curso (se agregó o eliminó alguno)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn reorder_modules(
+    State(state): State<AppState>,
+    instructor: RequireRole<Instructor>,
+    Path(course_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<ReorderModules>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+    lock_course_for_instructor(&mut tx, course_id, &instructor).await?;
+
+    // La lista debe tener exactamente los módulos actuales del curso
+    let current: HashSet<Uuid> = sqlx::query_scalar!("SELECT id FROM modules WHERE course_id = $1", course_id)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+    if current != payload.module_ids.iter().copied().collect() {
+        return Err(ApiError::Conflict(
+            "La lista no coincide con los módulos actuales del curso; vuelve a cargarlos".into(),
+        ));
+    }
+
+    sqlx::query!(
+        "UPDATE modules SET module_order = t.position::int
+         FROM UNNEST($2::uuid[]) WITH ORDINALITY AS t(id, position)
+         WHERE modules.id = t.id AND modules.course_id = $1",
+        course_id,
+        &payload.module_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let modules = sqlx::query_as!(
+        Module,
+        "SELECT id, course_id, module_name, module_slug, module_description, module_order,
+                module_status as \"module_status: _\", module_visibility as \"module_visibility: _\",
+                module_created_at, module_updated_at
+         FROM modules
+         WHERE course_id = $1
+         ORDER BY module_order",
+        course_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(modules))
+}
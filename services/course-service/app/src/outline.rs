@@ -0,0 +1,146 @@
+// --- Temario del curso ---
+//
+// `GET /api/v1/courses/{id}/outline` devuelve el curso con sus módulos y lecciones
+// anidados, para que el portal arme el temario en una sola petición. Se resuelve
+// con tres consultas (curso, módulos y lecciones) sin importar el tamaño del árbol.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use lms_common::{ApiError, Claims, ProblemDetails};
+
+use crate::modules::{ItemVisibility, PublishStatus};
+use crate::{can_manage_course, AppState, Course};
+
+/// Curso con sus módulos y lecciones en orden.
+#[derive(Serialize, ToSchema)]
+pub struct CourseOutline {
+    #[serde(flatten)]
+    course: Course,
+    modules: Vec<OutlineModule>,
+}
+
+/// Módulo dentro del temario.
+#[derive(Serialize, ToSchema)]
+pub struct OutlineModule {
+    id: Uuid,
+    module_name: String,
+    module_slug: String,
+    module_description: Option<String>,
+    module_order: i32,
+    module_status: PublishStatus,
+    module_visibility: ItemVisibility,
+    lessons: Vec<OutlineLesson>,
+}
+
+/// Lección dentro del temario, sin su contenido.
+#[derive(Serialize, ToSchema)]
+pub struct OutlineLesson {
+    id: Uuid,
+    #[serde(skip)]
+    module_id: Uuid,
+    lesson_name: String,
+    lesson_slug: String,
+    lesson_description: Option<String>,
+    lesson_order: i32,
+    lesson_status: PublishStatus,
+    lesson_visibility: ItemVisibility,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses/{id}/outline",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 200, description = "Curso con sus módulos y lecciones ordenados. Los borradores y ocultos (y las lecciones de módulos no publicados) solo los ve el instructor del curso o un administrador", body = CourseOutline),
+        (status = 401, description = "Token inválido", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado (o en borrador o revisión, para quien no lo administra)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        (),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_outline(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let course = sqlx::query_as!(
+        Course,
+        "SELECT id, instructor_id, course_name, course_slug, course_description,
+                course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                course_published_at, course_created_at
+         FROM courses WHERE id = $1 AND course_deleted_at IS NULL",
+        course_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await?
+    .filter(|course| course.is_visible_to(claims.as_ref()))
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+    let show_all = can_manage_course(claims.as_ref(), course.instructor_id);
+
+    let mut modules = sqlx::query!(
+        "SELECT id, module_name, module_slug, module_description, module_order,
+                module_status as \"module_status: PublishStatus\",
+                module_visibility as \"module_visibility: ItemVisibility\"
+         FROM modules
+         WHERE course_id = $1
+           AND ($2 OR (module_status = 'published' AND module_visibility = 'visible'))
+         ORDER BY module_order, module_created_at",
+        course_id,
+        show_all
+    )
+    .fetch_all(&state.db_pool)
+    .await?
+    .into_iter()
+    .map(|m| OutlineModule {
+        id: m.id,
+        module_name: m.module_name,
+        module_slug: m.module_slug,
+        module_description: m.module_description,
+        module_order: m.module_order,
+        module_status: m.module_status,
+        module_visibility: m.module_visibility,
+        lessons: Vec::new(),
+    })
+    .collect::<Vec<_>>();
+
+    // Todas las lecciones del curso de una vez; llegan ordenadas y se reparten por módulo
+    let lessons = sqlx::query_as!(
+        OutlineLesson,
+        "SELECT l.id, l.module_id, l.lesson_name, l.lesson_slug, l.lesson_description, l.lesson_order,
+                l.lesson_status as \"lesson_status: _\", l.lesson_visibility as \"lesson_visibility: _\"
+         FROM lessons l
+         JOIN modules m ON m.id = l.module_id
+         WHERE m.course_id = $1
+           AND ($2 OR (l.lesson_status = 'published' AND l.lesson_visibility = 'visible'))
+         ORDER BY l.lesson_order, l.lesson_created_at",
+        course_id,
+        show_all
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    let positions: HashMap<Uuid, usize> = modules.iter().enumerate().map(|(i, m)| (m.id, i)).collect();
+    for lesson in lessons {
+        // Las lecciones de módulos que el usuario no ve quedan fuera
+        if let Some(&i) = positions.get(&lesson.module_id) {
+            modules[i].lessons.push(lesson);
+        }
+    }
+
+    Ok(Json(CourseOutline { course, modules }))
+}
@@ -0,0 +1,68 @@
+// --- Caché local de la lista de revocación ---
+//
+// identity-service registra las revocaciones en la base de datos compartida
+// (`revoked_tokens` y `users.sessions_revoked_at`). Para no consultarla en cada
+// petición, el resultado de cada token se guarda durante `ttl`; una revocación
+// tarda como máximo ese tiempo en hacerse efectiva en este servicio.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct RevocationCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Uuid, (bool, Instant)>>,
+}
+
+impl RevocationCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Indica si el token `jti` del usuario `user_id`, emitido en `issued_at`
+    /// (segundos con fracción, ver `Claims::issued_at`), fue revocado.
+    pub async fn is_revoked(
+        &self,
+        db_pool: &PgPool,
+        jti: Uuid,
+        user_id: Uuid,
+        issued_at: f64,
+    ) -> Result<bool, sqlx::Error> {
+        if let Some(revoked) = self.cached(jti) {
+            return Ok(revoked);
+        }
+
+        // Misma comparación que identity-service, con milisegundos
+        let revoked = sqlx::query_scalar!(
+            "SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)
+                OR COALESCE((SELECT sessions_revoked_at > to_timestamp($3) FROM users WHERE id = $2), FALSE)
+                AS \"revoked!\"",
+            jti,
+            user_id,
+            issued_at
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        let mut entries = self.entries.lock().unwrap();
+        // Aprovechamos la escritura para descartar entradas vencidas.
+        entries.retain(|_, (_, checked_at)| checked_at.elapsed() < self.ttl);
+        entries.insert(jti, (revoked, Instant::now()));
+
+        Ok(revoked)
+    }
+
+    fn cached(&self, jti: Uuid) -> Option<bool> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(&jti)
+            .filter(|(_, checked_at)| checked_at.elapsed() < self.ttl)
+            .map(|(revoked, _)| *revoked)
+    }
+}
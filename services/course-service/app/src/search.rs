@@ -0,0 +1,223 @@
+// --- Búsqueda en el catálogo ---
+//
+// `GET /api/v1/courses/search?q=` busca en el nombre y la descripción del curso y en
+// los títulos de sus módulos y lecciones publicados y visibles. La columna `course_search` (tsvector con
+// raíces en español, con y sin tildes; ver `search_vector` y `search_query` en la
+// migración) la mantienen triggers de la base de datos en cada alta o cambio de
+// cursos, módulos y lecciones.
+
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use lms_common::{ApiError, Claims, ProblemDetails, Role};
+
+use crate::{AppState, Course};
+
+const DEFAULT_PER_PAGE: i64 = 20;
+const MAX_PER_PAGE: i64 = 50;
+const MAX_QUERY_LEN: usize = 200;
+
+/// Parámetros de la búsqueda de cursos.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchCoursesQuery {
+    /// Texto a buscar. Admite comillas para frases exactas, `or` y `-palabra` para excluir.
+    q: String,
+    /// Página, empezando en 1.
+    page: Option<i64>,
+    /// Resultados por página (máximo 50).
+    per_page: Option<i64>,
+}
+
+/// Curso encontrado, con su relevancia y un fragmento resaltado.
+#[derive(Serialize, ToSchema)]
+pub struct CourseSearchHit {
+    #[serde(flatten)]
+    course: Course,
+    /// Relevancia del resultado (mayor es mejor).
+    rank: f32,
+    /// Fragmento de la descripción o de los títulos del temario con las coincidencias
+    /// entre `<mark>` y `</mark>`. El resto del texto viene escapado como HTML.
+    #[schema(example = "Aprende los <mark>fundamentos</mark> de la programación en Rust")]
+    snippet: String,
+}
+
+/// Página de resultados de la búsqueda.
+#[derive(Serialize, ToSchema)]
+pub struct CourseSearchPage {
+    items: Vec<CourseSearchHit>,
+    page: i64,
+    per_page: i64,
+    /// Cursos que coinciden con la búsqueda.
+    total: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses/search",
+    params(SearchCoursesQuery),
+    responses(
+        (status = 200, description = "Cursos que coinciden, de mayor a menor relevancia. Se buscan los mismos cursos que muestra el listado al usuario", body = CourseSearchPage),
+        (status = 400, description = "Falta `q`, está vacío o es demasiado largo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Token inválido", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        (),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn search_courses(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Query(query): Query<SearchCoursesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Err(ApiError::BadRequest("El parámetro `q` no puede estar vacío".into()));
+    }
+    if q.chars().count() > MAX_QUERY_LEN {
+        return Err(ApiError::BadRequest(format!(
+            "El parámetro `q` admite como máximo {MAX_QUERY_LEN} caracteres"
+        )));
+    }
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+    let is_admin = claims.as_ref().is_some_and(|c| c.role == Role::Admin);
+    let user_id = claims.as_ref().map(|c| c.sub);
+
+    let total = sqlx::query_scalar!(
+        "SELECT COUNT(*) AS \"count!\"
+         FROM courses
+         WHERE course_search @@ search_query($1)
+           AND course_deleted_at IS NULL
+           AND ($2 OR instructor_id = $3 OR (course_status = 'published' AND course_visibility = 'public'))",
+        q,
+        is_admin,
+        user_id
+    )
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    let rows = sqlx::query!(
+        "WITH query AS (SELECT search_query($1) AS tsq)
+         SELECT c.id, c.instructor_id, c.course_name, c.course_slug, c.course_description,
+                c.course_status as \"course_status: crate::lifecycle::CourseStatus\",
+                c.course_visibility as \"course_visibility: crate::lifecycle::CourseVisibility\",
+                c.course_category, c.max_enrollments, c.enrollment_opens_at, c.enrollment_closes_at,
+                c.course_published_at, c.course_created_at,
+                ts_rank(c.course_search, query.tsq) AS \"rank!\",
+                ts_headline(
+                    'spanish_unaccent',
+                    concat_ws(' · ',
+                        c.course_description,
+                        (SELECT string_agg(m.module_name, ' · ' ORDER BY m.module_order)
+                         FROM modules m
+                         WHERE m.course_id = c.id
+                           AND m.module_status = 'published' AND m.module_visibility = 'visible'),
+                        (SELECT string_agg(l.lesson_name, ' · ' ORDER BY m.module_order, l.lesson_order)
+                         FROM lessons l JOIN modules m ON m.id = l.module_id
+                         WHERE m.course_id = c.id
+                           AND m.module_status = 'published' AND m.module_visibility = 'visible'
+                           AND l.lesson_status = 'published' AND l.lesson_visibility = 'visible')),
+                    query.tsq,
+                    'StartSel=<mark>, StopSel=</mark>, MaxWords=30, MinWords=10, MaxFragments=2, FragmentDelimiter=\" … \"'
+                ) AS \"snippet!\"
+         FROM courses c, query
+         WHERE c.course_search @@ query.tsq
+           AND c.course_deleted_at IS NULL
+           AND ($2 OR c.instructor_id = $3 OR (c.course_status = 'published' AND c.course_visibility = 'public'))
+         ORDER BY \"rank!\" DESC, c.course_created_at DESC, c.id
+         LIMIT $4 OFFSET $5",
+        q,
+        is_admin,
+        user_id,
+        per_page,
+        (page - 1) * per_page
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| CourseSearchHit {
+            course: Course {
+                id: row.id,
+                instructor_id: row.instructor_id,
+                course_name: row.course_name,
+                course_slug: row.course_slug,
+                course_description: row.course_description,
+                course_status: row.course_status,
+                course_visibility: row.course_visibility,
+                course_category: row.course_category,
+                max_enrollments: row.max_enrollments,
+                enrollment_opens_at: row.enrollment_opens_at,
+                enrollment_closes_at: row.enrollment_closes_at,
+                course_published_at: row.course_published_at,
+                course_created_at: row.course_created_at,
+            },
+            rank: row.rank,
+            snippet: escape_snippet(&row.snippet),
+        })
+        .collect();
+
+    Ok(Json(CourseSearchPage {
+        items,
+        page,
+        per_page,
+        total,
+    }))
+}
+
+/// Escapa el fragmento como HTML dejando solo las marcas de `ts_headline`. Los textos
+/// vienen de los instructores, así que cualquier otra etiqueta se muestra como texto.
+fn escape_snippet(snippet: &str) -> String {
+    let mut escaped = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+        .replace("&lt;mark&gt;", "<mark>")
+        .replace("&lt;/mark&gt;", "</mark>")
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    async fn matches(pool: &PgPool, text: &str, query: &str) -> bool {
+        sqlx::query_scalar("SELECT search_vector($1) @@ search_query($2)")
+            .bind(text)
+            .bind(query)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn matches_with_or_without_accents(pool: PgPool) {
+        assert!(matches(&pool, "Curso de programación en Python", "programacion").await);
+        assert!(matches(&pool, "Curso de programacion en Python", "programación").await);
+        assert!(matches(&pool, "Curso de programación en Python", "programa").await);
+    }
+
+    #[sqlx::test]
+    async fn exclusions_apply_with_or_without_accents(pool: PgPool) {
+        assert!(!matches(&pool, "Curso de programacion en Python", "python -programación").await);
+        assert!(!matches(&pool, "Curso de programación en Python", "python -programacion").await);
+        assert!(!matches(&pool, "Curso de programación en Python", "python -programación").await);
+        assert!(matches(&pool, "Curso de programación en Python", "python -java").await);
+    }
+}
@@ -0,0 +1,125 @@
+// --- Slugs para URLs ---
+
+use std::collections::HashSet;
+
+use lms_common::ApiError;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Largo máximo del slug, dejando margen en la columna VARCHAR(255) para sufijos.
+const MAX_SLUG_LEN: usize = 200;
+
+/// Convierte un nombre en un slug apto para URLs: minúsculas ASCII, sin acentos
+/// (`Introducción a Ñandú` → `introduccion-a-nandu`) y con guiones entre palabras.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        let c = transliterate(c);
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= MAX_SLUG_LEN {
+            break;
+        }
+    }
+
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "sin-titulo".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Quita tildes, diéresis y la virgulilla de la ñ; el resto de caracteres no cambia.
+fn transliterate(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ä' | 'ã' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'ö' | 'õ' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ñ' => 'n',
+        'ç' => 'c',
+        _ => c,
+    }
+}
+
+// --- Slugs únicos de cursos ---
+
+/// Genera un slug libre para el curso a partir de su nombre, agregando `-2`, `-3`, …
+/// si ya está en uso. Se consideran ocupados los slugs actuales y antiguos de los
+/// demás cursos (los antiguos siguen redirigiendo); los del propio curso se pueden reutilizar.
+/// Debe llamarse dentro de la transacción que guarda el slug.
+pub async fn unique_course_slug(
+    conn: &mut PgConnection,
+    name: &str,
+    course_id: Option<Uuid>,
+) -> Result<String, ApiError> {
+    let base = slugify(name);
+
+    // Serializa la generación de slugs con la misma base hasta el fin de la transacción
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", base)
+        .execute(&mut *conn)
+        .await?;
+
+    let pattern = format!("{base}-%");
+    let taken: HashSet<String> = sqlx::query_scalar!(
+        "SELECT course_slug AS \"slug!\" FROM courses
+         WHERE (course_slug = $1 OR course_slug LIKE $2) AND id IS DISTINCT FROM $3
+         UNION
+         SELECT old_slug FROM course_slug_redirects
+         WHERE (old_slug = $1 OR old_slug LIKE $2) AND course_id IS DISTINCT FROM $3",
+        base,
+        pattern,
+        course_id
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .collect();
+
+    if !taken.contains(&base) {
+        return Ok(base);
+    }
+    let slug = (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("siempre hay un sufijo libre");
+    Ok(slug)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transliterates_accents_and_enye() {
+        assert_eq!(slugify("Introducción a Ñandú"), "introduccion-a-nandu");
+        assert_eq!(slugify("Pingüino Àçôrê"), "pinguino-acore");
+        assert_eq!(slugify("Año de España"), "ano-de-espana");
+    }
+
+    #[test]
+    fn collapses_punctuation_and_spaces() {
+        assert_eq!(slugify("  ¿Rust: desde cero?  "), "rust-desde-cero");
+        assert_eq!(slugify("C++ / C# -- Básico!!!"), "c-c-basico");
+        assert_eq!(slugify("Python 3.12"), "python-3-12");
+    }
+
+    #[test]
+    fn falls_back_when_nothing_is_left() {
+        assert_eq!(slugify(""), "sin-titulo");
+        assert_eq!(slugify("   "), "sin-titulo");
+        assert_eq!(slugify("¡¿?!  -- ★"), "sin-titulo");
+    }
+
+    #[test]
+    fn caps_the_length() {
+        let slug = slugify(&"palabra ".repeat(60));
+        assert!(slug.len() <= MAX_SLUG_LEN);
+        assert!(!slug.ends_with('-'));
+    }
+}
@@ -0,0 +1,246 @@
+// --- Papelera de cursos ---
+//
+// `DELETE /api/v1/courses/{id}` no borra el curso: lo marca con `course_deleted_at`
+// y desaparece (junto con sus módulos y lecciones) de todos los endpoints. Durante
+// `COURSE_RETENTION_DAYS` días su instructor o un administrador pueden restaurarlo;
+// pasado ese plazo la purga lo borra definitivamente.
+
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use lms_common::auth::roles::Admin;
+use lms_common::{ApiError, Claims, ProblemDetails, RequireRole, Role};
+
+use crate::{can_manage_course, AppState, Course};
+
+/// Curso en la papelera.
+#[derive(Serialize, ToSchema)]
+pub struct DeletedCourse {
+    id: Uuid,
+    instructor_id: Uuid,
+    course_name: String,
+    course_slug: String,
+    course_deleted_at: DateTime<Utc>,
+    /// Usuario que lo eliminó.
+    course_deleted_by: Option<Uuid>,
+    /// A partir de esta fecha ya no se puede restaurar y la purga lo borra.
+    course_purge_at: DateTime<Utc>,
+}
+
+/// Resultado de una purga manual.
+#[derive(Serialize, ToSchema)]
+pub struct PurgeResult {
+    /// Cursos borrados definitivamente.
+    purged: u64,
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/courses/{id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 204, description = "Curso enviado a la papelera; se puede restaurar durante el plazo de retención"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Curso no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_course(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    let instructor_id = sqlx::query_scalar!(
+        "SELECT instructor_id FROM courses WHERE id = $1 AND course_deleted_at IS NULL FOR UPDATE",
+        course_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El curso no existe".into()))?;
+    if !can_manage_course(Some(&claims), instructor_id) {
+        return Err(ApiError::Forbidden(
+            "Solo el instructor del curso o un administrador puede eliminarlo".into(),
+        ));
+    }
+
+    sqlx::query!(
+        "UPDATE courses SET course_deleted_at = NOW(), course_deleted_by = $2 WHERE id = $1",
+        course_id,
+        claims.sub
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(course_id = %course_id, deleted_by = %claims.sub, "Curso enviado a la papelera");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/courses/{id}/restore",
+    params(
+        ("id" = Uuid, Path, description = "ID del curso")
+    ),
+    responses(
+        (status = 200, description = "Curso restaurado con sus módulos y lecciones", body = Course),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (no es el instructor del curso ni administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "El curso no existe o no está en la papelera", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Venció el plazo para restaurarlo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn restore_course(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(course_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    let deleted = sqlx::query!(
+        "SELECT instructor_id,
+                course_deleted_at < NOW() - make_interval(days => $2) AS \"expired!\"
+         FROM courses
+         WHERE id = $1 AND course_deleted_at IS NOT NULL
+         FOR UPDATE",
+        course_id,
+        state.course_retention_days
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El curso no está en la papelera".into()))?;
+    if !can_manage_course(Some(&claims), deleted.instructor_id) {
+        return Err(ApiError::Forbidden(
+            "Solo el instructor del curso o un administrador puede restaurarlo".into(),
+        ));
+    }
+    if deleted.expired {
+        return Err(ApiError::Conflict(format!(
+            "Venció el plazo de {} días para restaurar el curso",
+            state.course_retention_days
+        )));
+    }
+
+    let course = sqlx::query_as!(
+        Course,
+        "UPDATE courses SET course_deleted_at = NULL, course_deleted_by = NULL
+         WHERE id = $1
+         RETURNING id, instructor_id, course_name, course_slug, course_description,
+                   course_status as \"course_status: _\", course_visibility as \"course_visibility: _\",
+                   course_category, max_enrollments, enrollment_opens_at, enrollment_closes_at,
+                   course_published_at, course_created_at",
+        course_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(course_id = %course_id, restored_by = %claims.sub, "Curso restaurado");
+    Ok(Json(course))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/courses/trash",
+    responses(
+        (status = 200, description = "Cursos en la papelera: los propios, o todos para un administrador. Los más recientes primero", body = Vec<DeletedCourse>),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_deleted_courses(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, ApiError> {
+    let courses = sqlx::query_as!(
+        DeletedCourse,
+        "SELECT id, instructor_id, course_name, course_slug,
+                course_deleted_at AS \"course_deleted_at!\", course_deleted_by,
+                course_deleted_at + make_interval(days => $3) AS \"course_purge_at!\"
+         FROM courses
+         WHERE course_deleted_at IS NOT NULL AND ($1 OR instructor_id = $2)
+         ORDER BY course_deleted_at DESC",
+        claims.role == Role::Admin,
+        claims.sub,
+        state.course_retention_days
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(courses))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/courses/purge",
+    responses(
+        (status = 200, description = "Cursos cuyo plazo de retención venció, borrados definitivamente con sus módulos y lecciones", body = PurgeResult),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn purge_deleted_courses(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+) -> Result<impl IntoResponse, ApiError> {
+    let purged = purge_expired(&state.db_pool, state.course_retention_days).await?;
+    tracing::info!(purged, requested_by = %admin.sub, "Purga manual de cursos eliminados");
+    Ok(Json(PurgeResult { purged }))
+}
+
+/// Borra definitivamente los cursos que llevan en la papelera más del plazo de retención.
+/// Los módulos, lecciones y redirecciones de slug se borran en cascada.
+pub async fn purge_expired(db_pool: &PgPool, retention_days: i32) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM courses WHERE course_deleted_at < NOW() - make_interval(days => $1)",
+        retention_days
+    )
+    .execute(db_pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Ejecuta la purga periódicamente.
+pub fn spawn_purge_task(db_pool: PgPool, retention_days: i32, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            match purge_expired(&db_pool, retention_days).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!(purged, "Cursos eliminados purgados"),
+                Err(e) => tracing::error!("Error al purgar cursos eliminados: {:?}", e),
+            }
+        }
+    });
+}
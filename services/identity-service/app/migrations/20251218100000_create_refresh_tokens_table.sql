@@ -0,0 +1,16 @@
+-- Crear la tabla de refresh tokens
+-- Solo se guarda el hash SHA-256 del token opaco que recibe el cliente.
+-- Todos los tokens obtenidos por rotación desde un mismo login comparten `family_id`.
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    replaced_by UUID REFERENCES refresh_tokens(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
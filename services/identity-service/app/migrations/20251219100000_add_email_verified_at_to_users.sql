@@ -0,0 +1,2 @@
+-- Fecha en que el usuario confirmó su dirección de email (NULL = sin verificar)
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,22 @@
+-- Segundo factor de autenticación (TOTP, RFC 6238)
+-- `enabled_at` queda en NULL mientras el usuario no confirme el primer código.
+-- `last_used_step` guarda el último intervalo de 30 s aceptado, para que un
+-- mismo código no pueda usarse dos veces.
+CREATE TABLE user_mfa (
+    user_id UUID PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    totp_secret VARCHAR(255) NOT NULL,
+    enabled_at TIMESTAMP WITH TIME ZONE,
+    last_used_step BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Códigos de recuperación de un solo uso (solo se guarda su hash SHA-256)
+CREATE TABLE mfa_recovery_codes (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_mfa_recovery_codes_user_id ON mfa_recovery_codes(user_id);
@@ -0,0 +1,13 @@
+-- Intentos fallidos de login, por cuenta (email normalizado) y por IP de origen.
+-- Las cuentas se identifican por el email enviado, exista o no, para que el
+-- bloqueo se comporte igual con emails desconocidos.
+CREATE TABLE login_failures (
+    scope VARCHAR(16) NOT NULL CHECK (scope IN ('account', 'ip')),
+    key VARCHAR(255) NOT NULL,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    locked_until TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (scope, key)
+);
+
+CREATE INDEX idx_login_failures_locked_until ON login_failures(locked_until);
@@ -0,0 +1,15 @@
+-- Cuentas desactivadas por un administrador: no pueden iniciar sesión
+ALTER TABLE users ADD COLUMN deactivated_at TIMESTAMP WITH TIME ZONE;
+
+-- Registro de cada cambio de rol. Sin clave foránea a `users` para que el
+-- historial se conserve aunque se elimine el usuario o el administrador.
+CREATE TABLE user_role_changes (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    user_id UUID NOT NULL,
+    old_role user_role NOT NULL,
+    new_role user_role NOT NULL,
+    changed_by UUID NOT NULL,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_user_role_changes_user_id ON user_role_changes(user_id);
@@ -0,0 +1,15 @@
+-- Estado y visibilidad de módulos (y más adelante de lecciones) como tipos ENUM.
+-- Hasta ahora eran VARCHAR sin uso; los valores desconocidos pasan a 'draft' / 'visible'.
+CREATE TYPE publish_status AS ENUM ('draft', 'published');
+CREATE TYPE item_visibility AS ENUM ('visible', 'hidden');
+
+ALTER TABLE modules
+    ALTER COLUMN module_status TYPE publish_status
+        USING (CASE WHEN module_status = 'published' THEN 'published' ELSE 'draft' END)::publish_status,
+    ALTER COLUMN module_status SET DEFAULT 'draft',
+    ALTER COLUMN module_visibility TYPE item_visibility
+        USING (CASE WHEN module_visibility = 'hidden' THEN 'hidden' ELSE 'visible' END)::item_visibility,
+    ALTER COLUMN module_visibility SET DEFAULT 'visible';
+
+-- Listado de los módulos de un curso en orden
+CREATE INDEX idx_modules_course_id_order ON modules(course_id, module_order);
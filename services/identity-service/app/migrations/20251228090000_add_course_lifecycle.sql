@@ -0,0 +1,22 @@
+-- Ciclo de vida de los cursos: borrador → revisión → publicado → archivado.
+-- Hasta ahora eran VARCHAR sin uso; los valores desconocidos pasan a 'draft' / 'public'.
+CREATE TYPE course_status AS ENUM ('draft', 'review', 'published', 'archived');
+CREATE TYPE course_visibility AS ENUM ('public', 'unlisted');
+
+ALTER TABLE courses
+    ALTER COLUMN course_status DROP DEFAULT,
+    ALTER COLUMN course_visibility DROP DEFAULT;
+
+ALTER TABLE courses
+    ALTER COLUMN course_status TYPE course_status
+        USING (CASE WHEN course_status IN ('review', 'published', 'archived') THEN course_status ELSE 'draft' END)::course_status,
+    ALTER COLUMN course_status SET DEFAULT 'draft',
+    ALTER COLUMN course_visibility TYPE course_visibility
+        USING (CASE WHEN course_visibility = 'unlisted' THEN 'unlisted' ELSE 'public' END)::course_visibility,
+    ALTER COLUMN course_visibility SET DEFAULT 'public',
+    ADD COLUMN course_published_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE courses SET course_published_at = course_updated_at WHERE course_status = 'published';
+
+-- Catálogo público: cursos publicados y listados
+CREATE INDEX idx_courses_status_visibility ON courses (course_status, course_visibility);
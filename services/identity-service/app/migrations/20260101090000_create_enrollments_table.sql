@@ -0,0 +1,18 @@
+-- Inscripciones de estudiantes en cursos. Una fila por estudiante y curso: al
+-- desinscribirse queda como 'dropped' y se reactiva si vuelve a inscribirse.
+CREATE TYPE enrollment_status AS ENUM ('active', 'completed', 'dropped');
+
+CREATE TABLE enrollments (
+    id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    student_id UUID NOT NULL,
+    enrollment_status enrollment_status NOT NULL DEFAULT 'active',
+    enrolled_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    dropped_at TIMESTAMP WITH TIME ZONE,
+    enrollment_updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_enrollments_course_student UNIQUE (course_id, student_id)
+);
+
+-- "Mis cursos" de un estudiante
+CREATE INDEX idx_enrollments_student ON enrollments (student_id, enrolled_at DESC);
@@ -0,0 +1,16 @@
+-- Cupo, periodo de inscripción y lista de espera de los cursos.
+-- NULL significa sin límite de plazas / sin fecha de apertura o cierre.
+ALTER TABLE courses
+    ADD COLUMN max_enrollments INT CHECK (max_enrollments > 0),
+    ADD COLUMN enrollment_opens_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN enrollment_closes_at TIMESTAMP WITH TIME ZONE,
+    ADD CONSTRAINT chk_courses_enrollment_window
+        CHECK (enrollment_opens_at IS NULL OR enrollment_closes_at IS NULL OR enrollment_opens_at < enrollment_closes_at);
+
+-- Con el curso lleno, las nuevas inscripciones esperan turno por orden de llegada
+ALTER TYPE enrollment_status ADD VALUE 'waitlisted';
+
+ALTER TABLE enrollments ADD COLUMN waitlisted_at TIMESTAMP WITH TIME ZONE;
+
+-- Plazas ocupadas y siguiente de la lista de espera
+CREATE INDEX idx_enrollments_course_status ON enrollments (course_id, enrollment_status, waitlisted_at);
@@ -0,0 +1,47 @@
+-- Las invitaciones pendientes solo se reclaman cuando el titular demostró que la
+-- dirección es suya: registrarse con un email invitado no basta para entrar a un
+-- curso privado. identity-service llama a la función al verificar el email y al
+-- confirmar un cambio de email; solo inscribe a estudiantes.
+CREATE OR REPLACE FUNCTION claim_course_invitations(p_user_id UUID, p_email TEXT) RETURNS INT
+LANGUAGE plpgsql AS $$
+DECLARE
+    inv RECORD;
+    v_max INT;
+    v_taken BIGINT;
+    v_full BOOLEAN;
+    v_claimed INT := 0;
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM users
+        WHERE id = p_user_id AND email = lower(p_email)
+          AND role = 'student' AND email_verified_at IS NOT NULL AND deactivated_at IS NULL
+    ) THEN
+        RETURN 0;
+    END IF;
+
+    FOR inv IN
+        SELECT i.id, i.course_id
+        FROM course_invitations i JOIN courses c ON c.id = i.course_id
+        WHERE i.email = lower(p_email)
+          AND i.accepted_at IS NULL AND i.revoked_at IS NULL AND i.expires_at > NOW()
+          AND c.course_status = 'published' AND c.course_deleted_at IS NULL
+        ORDER BY i.created_at
+    LOOP
+        SELECT max_enrollments INTO v_max FROM courses WHERE id = inv.course_id FOR UPDATE;
+        SELECT COUNT(*) INTO v_taken FROM enrollments
+        WHERE course_id = inv.course_id AND enrollment_status IN ('active', 'completed');
+        v_full := v_max IS NOT NULL AND v_taken >= v_max;
+
+        INSERT INTO enrollments (course_id, student_id, enrollment_status, waitlisted_at)
+        VALUES (inv.course_id, p_user_id,
+                (CASE WHEN v_full THEN 'waitlisted' ELSE 'active' END)::enrollment_status,
+                CASE WHEN v_full THEN NOW() END)
+        ON CONFLICT (course_id, student_id) DO NOTHING;
+
+        UPDATE course_invitations SET accepted_at = NOW(), accepted_by = p_user_id WHERE id = inv.id;
+        v_claimed := v_claimed + 1;
+    END LOOP;
+
+    RETURN v_claimed;
+END;
+$$;
@@ -0,0 +1,431 @@
+// --- Administración de usuarios ---
+//
+// Endpoints solo para administradores (extractor `RequireRole<Admin>`). Cambiar el rol
+// o desactivar una cuenta cierra todas sus sesiones, ya que el rol viaja dentro
+// de los access tokens. Cada cambio de rol queda registrado en `user_role_changes`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use lms_common::auth::roles::Admin;
+use lms_common::{ApiError, ProblemDetails, RequireRole, Role};
+
+use crate::tokens::revoke_all_sessions;
+use crate::AppState;
+
+const DEFAULT_PER_PAGE: i64 = 20;
+const MAX_PER_PAGE: i64 = 100;
+
+/// Usuario tal como lo ve un administrador.
+#[derive(Serialize, ToSchema)]
+pub struct AdminUserResponse {
+    id: Uuid,
+    first_name: String,
+    last_name: String,
+    username: String,
+    email: String,
+    role: Role,
+    email_verified_at: Option<DateTime<Utc>>,
+    mfa_enabled: bool,
+    /// Fecha de desactivación; `null` si la cuenta está activa.
+    deactivated_at: Option<DateTime<Utc>>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+/// Parámetros de búsqueda y paginación del listado de usuarios.
+#[derive(Deserialize, IntoParams)]
+pub struct ListUsersQuery {
+    /// Página, empezando en 1.
+    page: Option<i64>,
+    /// Usuarios por página (máximo 100).
+    per_page: Option<i64>,
+    /// Texto a buscar en nombre, apellido, username o email (sin distinguir mayúsculas).
+    q: Option<String>,
+    /// Filtrar por rol.
+    role: Option<Role>,
+}
+
+/// Página de resultados del listado de usuarios.
+#[derive(Serialize, ToSchema)]
+pub struct UserPage {
+    items: Vec<AdminUserResponse>,
+    page: i64,
+    per_page: i64,
+    /// Total de usuarios que cumplen el filtro.
+    total: i64,
+}
+
+/// Payload para cambiar el rol de un usuario.
+#[derive(Deserialize, ToSchema)]
+pub struct ChangeRolePayload {
+    role: Role,
+}
+
+/// Un cambio de rol registrado.
+#[derive(Serialize, ToSchema)]
+pub struct RoleChange {
+    id: Uuid,
+    old_role: Role,
+    new_role: Role,
+    /// Administrador que hizo el cambio.
+    changed_by: Uuid,
+    changed_at: DateTime<Utc>,
+}
+
+/// Escapa los comodines de `LIKE` para buscar el texto tal cual.
+fn like_pattern(q: &str) -> String {
+    let escaped = q
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// El usuario, o 404 si no existe.
+async fn fetch_user(state: &AppState, id: Uuid) -> Result<AdminUserResponse, ApiError> {
+    sqlx::query_as!(
+        AdminUserResponse,
+        "SELECT u.id, u.first_name, u.last_name, u.username, u.email, u.role as \"role: _\",
+                u.email_verified_at, u.deactivated_at, u.created_at, u.updated_at,
+                EXISTS (SELECT 1 FROM user_mfa m WHERE m.user_id = u.id AND m.enabled_at IS NOT NULL) AS \"mfa_enabled!\"
+         FROM users u WHERE u.id = $1",
+        id
+    )
+    .fetch_optional(&state.db_pool)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El usuario no existe".into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users",
+    params(ListUsersQuery),
+    responses(
+        (status = 200, description = "Página de usuarios, ordenados por fecha de creación (más recientes primero)", body = UserPage),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para listar usuarios con búsqueda y paginación (solo administradores)
+pub async fn list_users(
+    State(state): State<AppState>,
+    _admin: RequireRole<Admin>,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+    let pattern = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()).map(like_pattern);
+
+    let total = sqlx::query_scalar!(
+        "SELECT COUNT(*) AS \"count!\" FROM users
+         WHERE ($1::text IS NULL OR first_name ILIKE $1 OR last_name ILIKE $1 OR username ILIKE $1 OR email ILIKE $1)
+           AND ($2::user_role IS NULL OR role = $2)",
+        pattern,
+        query.role as Option<Role>
+    )
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    let items = sqlx::query_as!(
+        AdminUserResponse,
+        "SELECT u.id, u.first_name, u.last_name, u.username, u.email, u.role as \"role: _\",
+                u.email_verified_at, u.deactivated_at, u.created_at, u.updated_at,
+                EXISTS (SELECT 1 FROM user_mfa m WHERE m.user_id = u.id AND m.enabled_at IS NOT NULL) AS \"mfa_enabled!\"
+         FROM users u
+         WHERE ($1::text IS NULL OR u.first_name ILIKE $1 OR u.last_name ILIKE $1 OR u.username ILIKE $1 OR u.email ILIKE $1)
+           AND ($2::user_role IS NULL OR u.role = $2)
+         ORDER BY u.created_at DESC, u.id
+         LIMIT $3 OFFSET $4",
+        pattern,
+        query.role as Option<Role>,
+        per_page,
+        (page - 1) * per_page
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(UserPage { items, page, per_page, total }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users/{id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del usuario")
+    ),
+    responses(
+        (status = 200, description = "Usuario encontrado", body = AdminUserResponse),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Usuario no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para obtener un usuario (solo administradores)
+pub async fn get_user(
+    State(state): State<AppState>,
+    _admin: RequireRole<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(fetch_user(&state, id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/users/{id}/role",
+    params(
+        ("id" = Uuid, Path, description = "ID del usuario")
+    ),
+    request_body = ChangeRolePayload,
+    responses(
+        (status = 200, description = "Rol actualizado; las sesiones del usuario se cerraron", body = AdminUserResponse),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Usuario no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Un administrador no puede cambiar su propio rol", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para cambiar el rol de un usuario (solo administradores)
+pub async fn change_role(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ChangeRolePayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    // Evita que el último administrador se quite el rol por accidente.
+    if id == admin.sub {
+        return Err(ApiError::Conflict("No puedes cambiar tu propio rol".into()));
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+
+    let old_role = sqlx::query_scalar!(
+        "SELECT role as \"role: Role\" FROM users WHERE id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::NotFound("El usuario no existe".into()))?;
+
+    if old_role != payload.role {
+        sqlx::query!(
+            "UPDATE users SET role = $2, updated_at = NOW() WHERE id = $1",
+            id,
+            payload.role as Role
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO user_role_changes (user_id, old_role, new_role, changed_by) VALUES ($1, $2, $3, $4)",
+            id,
+            old_role as Role,
+            payload.role as Role,
+            admin.sub
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Los tokens vigentes llevan el rol anterior.
+        revoke_all_sessions(&mut tx, id).await?;
+
+        tracing::info!(user_id = %id, changed_by = %admin.sub, ?old_role, new_role = ?payload.role, "Rol de usuario cambiado");
+    }
+
+    tx.commit().await?;
+    Ok(Json(fetch_user(&state, id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users/{id}/role-changes",
+    params(
+        ("id" = Uuid, Path, description = "ID del usuario")
+    ),
+    responses(
+        (status = 200, description = "Historial de cambios de rol, del más reciente al más antiguo", body = [RoleChange]),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para consultar el historial de roles de un usuario (solo administradores)
+pub async fn list_role_changes(
+    State(state): State<AppState>,
+    _admin: RequireRole<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let changes = sqlx::query_as!(
+        RoleChange,
+        "SELECT id, old_role as \"old_role: _\", new_role as \"new_role: _\", changed_by, changed_at
+         FROM user_role_changes WHERE user_id = $1
+         ORDER BY changed_at DESC",
+        id
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(changes))
+}
+
+/// Activa o desactiva una cuenta. Al desactivarla se cierran todas sus sesiones.
+async fn set_deactivated(state: &AppState, admin: Uuid, id: Uuid, deactivate: bool) -> Result<StatusCode, ApiError> {
+    if id == admin && deactivate {
+        return Err(ApiError::Conflict("No puedes desactivar tu propia cuenta".into()));
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+
+    let updated = sqlx::query!(
+        "UPDATE users
+         SET deactivated_at = CASE WHEN $2 THEN COALESCE(deactivated_at, NOW()) ELSE NULL END,
+             updated_at = NOW()
+         WHERE id = $1",
+        id,
+        deactivate
+    )
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(ApiError::NotFound("El usuario no existe".into()));
+    }
+
+    if deactivate {
+        revoke_all_sessions(&mut tx, id).await?;
+    }
+
+    tx.commit().await?;
+
+    tracing::info!(user_id = %id, changed_by = %admin, deactivate, "Estado de cuenta cambiado");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/deactivate",
+    params(
+        ("id" = Uuid, Path, description = "ID del usuario")
+    ),
+    responses(
+        (status = 204, description = "Cuenta desactivada; sus sesiones se cerraron y no puede iniciar sesión"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Usuario no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Un administrador no puede desactivar su propia cuenta", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para desactivar una cuenta (solo administradores)
+pub async fn deactivate_user(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    set_deactivated(&state, admin.sub, id, true).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/reactivate",
+    params(
+        ("id" = Uuid, Path, description = "ID del usuario")
+    ),
+    responses(
+        (status = 204, description = "Cuenta reactivada"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Usuario no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para reactivar una cuenta desactivada (solo administradores)
+pub async fn reactivate_user(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    set_deactivated(&state, admin.sub, id, false).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/users/{id}",
+    params(
+        ("id" = Uuid, Path, description = "ID del usuario")
+    ),
+    responses(
+        (status = 204, description = "Usuario eliminado junto con sus sesiones y tokens"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Usuario no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El usuario es instructor de algún curso, o es el propio administrador", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para eliminar un usuario (solo administradores)
+pub async fn delete_user(
+    State(state): State<AppState>,
+    admin: RequireRole<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    if id == admin.sub {
+        return Err(ApiError::Conflict("No puedes eliminar tu propia cuenta".into()));
+    }
+
+    // `courses.instructor_id` no tiene clave foránea (la tabla es de course-service),
+    // así que se comprueba aquí para no dejar cursos sin instructor.
+    let deleted = sqlx::query!(
+        "DELETE FROM users
+         WHERE id = $1 AND NOT EXISTS (SELECT 1 FROM courses WHERE instructor_id = $1)
+         RETURNING id",
+        id
+    )
+    .fetch_optional(&state.db_pool)
+    .await?;
+
+    if deleted.is_none() {
+        let exists = sqlx::query_scalar!("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1) AS \"exists!\"", id)
+            .fetch_one(&state.db_pool)
+            .await?;
+        return Err(if exists {
+            ApiError::Conflict("El usuario es instructor de uno o más cursos; reasígnalos o elimínalos primero".into())
+        } else {
+            ApiError::NotFound("El usuario no existe".into())
+        });
+    }
+
+    tracing::info!(user_id = %id, deleted_by = %admin.sub, "Usuario eliminado");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,54 @@
+// --- Verificación de access tokens ---
+//
+// El extractor de `Claims` y los guards por rol viven en `lms-common`; aquí
+// `AppState` implementa `AuthState` con las claves propias del servicio.
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use lms_common::auth::AuthState;
+use lms_common::Claims;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::AppState;
+
+/// Indica si el token fue revocado, ya sea individualmente (logout) o porque
+/// se cerraron todas las sesiones del usuario después de emitirlo.
+///
+/// `issued_at` es el momento de emisión con milisegundos (`Claims::issued_at`): un
+/// token emitido en el mismo segundo que la revocación, pero antes, también cae.
+pub async fn is_token_revoked(
+    db_pool: &PgPool,
+    jti: Uuid,
+    user_id: Uuid,
+    issued_at: f64,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)
+            OR COALESCE((SELECT sessions_revoked_at > to_timestamp($3) FROM users WHERE id = $2), FALSE)
+            AS \"revoked!\"",
+        jti,
+        user_id,
+        issued_at
+    )
+    .fetch_one(db_pool)
+    .await
+}
+
+#[async_trait]
+impl AuthState for AppState {
+    async fn verify_access_token(&self, token: &str) -> Result<Claims, StatusCode> {
+        // Verificar firma y expiración con las claves propias
+        let claims: Claims = self.keys.verify(token, None).ok_or(StatusCode::UNAUTHORIZED)?;
+
+        // Rechazar tokens revocados
+        match is_token_revoked(&self.db_pool, claims.jti, claims.sub, claims.issued_at()).await {
+            Ok(false) => Ok(claims),
+            Ok(true) => Err(StatusCode::UNAUTHORIZED),
+            Err(e) => {
+                tracing::error!("Error al consultar revocaciones: {:?}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
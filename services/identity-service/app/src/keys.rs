@@ -0,0 +1,170 @@
+// --- Claves de firma de los JWT (EdDSA / Ed25519) ---
+//
+// Las claves privadas se leen de `JWT_KEYS_DIR`: cada archivo `<kid>.pem` es una
+// clave Ed25519 en formato PKCS#8 y su nombre (sin extensión) es el `kid`.
+// Todas las claves del directorio se publican en `/.well-known/jwks.json` y se
+// aceptan al verificar, pero solo la activa (`JWT_ACTIVE_KID`, o la última en
+// orden alfabético) firma tokens nuevos.
+//
+// Rotación: agregar la clave nueva y activarla; retirar la anterior recién
+// cuando hayan expirado los access tokens que firmó.
+
+use std::fs;
+use std::path::Path;
+
+use axum::{extract::State, Json};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::pkcs8::{spki::der::pem::LineEnding, DecodePrivateKey, EncodePrivateKey};
+use ed25519_dalek::SigningKey;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse,
+};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::AppState;
+
+struct KeyEntry {
+    kid: String,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    jwk: Jwk,
+}
+
+pub struct SigningKeys {
+    active: usize,
+    keys: Vec<KeyEntry>,
+}
+
+impl SigningKeys {
+    /// Carga las claves de `dir`. Si no hay ninguna, genera una y la guarda ahí
+    /// para que los tokens sigan siendo válidos tras un reinicio.
+    pub fn load_or_generate(dir: &Path, active_kid: Option<&str>) -> Self {
+        let mut keys = Vec::new();
+
+        if let Ok(entries) = fs::read_dir(dir) {
+            let mut paths: Vec<_> = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "pem"))
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                let kid = path.file_stem().unwrap().to_string_lossy().into_owned();
+                let pem = fs::read_to_string(&path)
+                    .unwrap_or_else(|e| panic!("No se pudo leer la clave {}: {e}", path.display()));
+                let signing_key = SigningKey::from_pkcs8_pem(&pem)
+                    .unwrap_or_else(|e| panic!("Clave Ed25519 inválida en {}: {e}", path.display()));
+                keys.push(KeyEntry::new(kid, &signing_key));
+            }
+        }
+
+        if keys.is_empty() {
+            let kid = chrono::Utc::now().format("%Y%m%d%H%M%S").to_string();
+            let signing_key = SigningKey::generate(&mut rand::rngs::OsRng);
+            let pem = signing_key
+                .to_pkcs8_pem(LineEnding::LF)
+                .expect("No se pudo serializar la clave generada");
+
+            fs::create_dir_all(dir).expect("No se pudo crear JWT_KEYS_DIR");
+            fs::write(dir.join(format!("{kid}.pem")), pem.as_bytes())
+                .expect("No se pudo guardar la clave generada");
+            tracing::warn!("No había claves de firma en {}, se generó '{}'", dir.display(), kid);
+
+            keys.push(KeyEntry::new(kid, &signing_key));
+        }
+
+        let active = match active_kid {
+            Some(kid) => keys
+                .iter()
+                .position(|k| k.kid == kid)
+                .unwrap_or_else(|| panic!("JWT_ACTIVE_KID '{kid}' no existe en JWT_KEYS_DIR")),
+            None => keys.len() - 1,
+        };
+
+        tracing::info!(
+            "Claves de firma cargadas: {} (activa: '{}')",
+            keys.len(),
+            keys[active].kid
+        );
+
+        Self { active, keys }
+    }
+
+    /// Firma `claims` con la clave activa.
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, jsonwebtoken::errors::Error> {
+        let key = &self.keys[self.active];
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(key.kid.clone());
+        encode(&header, claims, &key.encoding)
+    }
+
+    /// Verifica firma y expiración de un token firmado por cualquiera de las claves cargadas.
+    ///
+    /// Los tokens de propósito específico (verificación de email, etc.) llevan un
+    /// `aud` propio y solo se aceptan pasando ese mismo `audience`; los access
+    /// tokens no llevan `aud`, así que uno no puede usarse en lugar del otro.
+    pub fn verify<T: DeserializeOwned>(&self, token: &str, audience: Option<&str>) -> Option<T> {
+        let kid = decode_header(token).ok()?.kid?;
+        let key = self.keys.iter().find(|k| k.kid == kid)?;
+
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        if let Some(audience) = audience {
+            validation.set_audience(&[audience]);
+            validation.set_required_spec_claims(&["exp", "aud"]);
+        }
+
+        decode::<T>(token, &key.decoding, &validation)
+            .ok()
+            .map(|data| data.claims)
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.iter().map(|k| k.jwk.clone()).collect(),
+        }
+    }
+}
+
+impl KeyEntry {
+    fn new(kid: String, signing_key: &SigningKey) -> Self {
+        let der = signing_key
+            .to_pkcs8_der()
+            .expect("No se pudo serializar la clave Ed25519");
+        let x = URL_SAFE_NO_PAD.encode(signing_key.verifying_key().as_bytes());
+
+        let jwk = Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                key_algorithm: Some(KeyAlgorithm::EdDSA),
+                key_id: Some(kid.clone()),
+                ..Default::default()
+            },
+            algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: x.clone(),
+            }),
+        };
+
+        Self {
+            encoding: EncodingKey::from_ed_der(der.as_bytes()),
+            decoding: DecodingKey::from_ed_components(&x).expect("Clave pública Ed25519 inválida"),
+            kid,
+            jwk,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses(
+        (status = 200, description = "Claves públicas para verificar los JWT emitidos (JWK Set)", body = Object)
+    )
+)]
+/// Handler que publica el JWK Set con las claves públicas vigentes
+pub async fn jwks(State(state): State<AppState>) -> Json<JwkSet> {
+    Json(state.keys.jwks())
+}
@@ -0,0 +1,347 @@
+// --- Protección contra fuerza bruta en el login ---
+//
+// Los intentos fallidos se cuentan por cuenta (su email; si no existe, el
+// identificador enviado, normalizado) y por IP de origen, dentro de una ventana
+// de `LOGIN_LOCKOUT_MINUTES`:
+// - Cuenta: tras cada fallo hay que esperar 1, 2, 4, 8... segundos antes del
+//   siguiente intento; al llegar a `LOGIN_MAX_FAILED_ATTEMPTS` se bloquea.
+// - IP: se bloquea al llegar a `LOGIN_MAX_FAILED_ATTEMPTS_PER_IP`.
+// Mientras dure la espera o el bloqueo se responde 429 con `Retry-After`, sin
+// llegar a verificar la contraseña. Un login correcto limpia el contador de la cuenta.
+
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, Path, State},
+    http::{request::Parts, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use lms_common::auth::roles::Admin;
+use lms_common::config::env_or;
+use lms_common::tokens::generate_token;
+use lms_common::{ApiError, ProblemDetails, RequireRole};
+
+use crate::{hash_password, AppState};
+
+const MAX_DELAY_SECONDS: i64 = 60;
+
+/// Configuración del bloqueo de login, leída del entorno.
+pub struct LoginGuard {
+    max_account_attempts: i32,
+    max_ip_attempts: i32,
+    lockout: Duration,
+    trust_forwarded_for: bool,
+    /// Hash de una contraseña aleatoria. Se verifica contra él cuando el email no
+    /// existe, para que la respuesta tarde lo mismo que con un usuario real.
+    dummy_hash: String,
+}
+
+impl LoginGuard {
+    pub fn from_env() -> Self {
+        let dummy_password = generate_token();
+        Self {
+            max_account_attempts: env_or("LOGIN_MAX_FAILED_ATTEMPTS", 5),
+            max_ip_attempts: env_or("LOGIN_MAX_FAILED_ATTEMPTS_PER_IP", 20),
+            lockout: Duration::minutes(env_or("LOGIN_LOCKOUT_MINUTES", 15)),
+            trust_forwarded_for: env::var("TRUST_FORWARDED_FOR").is_ok_and(|v| v == "true"),
+            dummy_hash: hash_password(&dummy_password).expect("No se pudo generar el hash de referencia"),
+        }
+    }
+
+    /// Verifica `password` contra un hash que nunca coincide (mismo costo que una verificación real).
+    pub fn verify_dummy(&self, password: &str) {
+        let _ = argon2::verify_encoded(&self.dummy_hash, password.as_bytes());
+    }
+
+    /// Espera progresiva tras `failed_attempts` fallos consecutivos de una cuenta.
+    fn delay(failed_attempts: i32) -> Duration {
+        let exponent = (failed_attempts - 1).clamp(0, 16) as u32;
+        Duration::seconds((1i64 << exponent).min(MAX_DELAY_SECONDS))
+    }
+
+    /// Devuelve cuánto falta para poder intentar de nuevo, o `None` si se puede ya.
+    pub async fn retry_after(
+        &self,
+        pool: &PgPool,
+        account: Option<&str>,
+        ip: &str,
+    ) -> Result<Option<Duration>, sqlx::Error> {
+        let rows = sqlx::query!(
+            "SELECT scope, failed_attempts, last_failed_at, locked_until FROM login_failures
+             WHERE (scope = 'account' AND key = $1) OR (scope = 'ip' AND key = $2)",
+            account,
+            ip
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let now = Utc::now();
+        let wait = rows
+            .iter()
+            .filter_map(|row| {
+                let mut until = row.locked_until;
+                if row.scope == "account" && row.last_failed_at > now - self.lockout {
+                    let delayed = row.last_failed_at + Self::delay(row.failed_attempts);
+                    until = until.max(Some(delayed));
+                }
+                until.filter(|u| *u > now).map(|u| u - now)
+            })
+            .max();
+
+        Ok(wait)
+    }
+
+    /// Registra un intento fallido y bloquea la cuenta o la IP si llegaron al máximo.
+    pub async fn record_failure(
+        &self,
+        pool: &PgPool,
+        account: Option<&str>,
+        ip: &str,
+    ) -> Result<(), sqlx::Error> {
+        if let Some(account) = account {
+            self.increment(pool, "account", account, self.max_account_attempts).await?;
+        }
+        self.increment(pool, "ip", ip, self.max_ip_attempts).await
+    }
+
+    async fn increment(
+        &self,
+        pool: &PgPool,
+        scope: &str,
+        key: &str,
+        max_attempts: i32,
+    ) -> Result<(), sqlx::Error> {
+        let window = self.lockout.num_seconds() as f64;
+
+        // Los fallos más antiguos que la ventana no cuentan: el contador vuelve a 1.
+        let failed_attempts = sqlx::query_scalar!(
+            "INSERT INTO login_failures (scope, key, failed_attempts, last_failed_at) VALUES ($1, $2, 1, NOW())
+             ON CONFLICT (scope, key) DO UPDATE SET
+                 failed_attempts = CASE
+                     WHEN login_failures.last_failed_at < NOW() - make_interval(secs => $3) THEN 1
+                     ELSE login_failures.failed_attempts + 1
+                 END,
+                 last_failed_at = NOW()
+             RETURNING failed_attempts",
+            scope,
+            key,
+            window
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if failed_attempts >= max_attempts {
+            sqlx::query!(
+                "UPDATE login_failures SET locked_until = NOW() + make_interval(secs => $3)
+                 WHERE scope = $1 AND key = $2",
+                scope,
+                key,
+                window
+            )
+            .execute(pool)
+            .await?;
+            tracing::warn!(scope, key, failed_attempts, "Login bloqueado por intentos fallidos");
+        }
+
+        Ok(())
+    }
+
+    /// Limpia el contador de una cuenta tras un login correcto.
+    pub async fn clear_account(&self, pool: &PgPool, account: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM login_failures WHERE scope = 'account' AND key = $1",
+            account
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Borra periódicamente los registros vencidos.
+    pub fn spawn_cleanup_task(self: Arc<Self>, pool: PgPool) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let result = sqlx::query!(
+                    "DELETE FROM login_failures
+                     WHERE last_failed_at < NOW() - make_interval(secs => $1)
+                       AND (locked_until IS NULL OR locked_until < NOW())",
+                    self.lockout.num_seconds() as f64
+                )
+                .execute(&pool)
+                .await;
+                if let Err(e) = result {
+                    tracing::error!("Error al limpiar intentos de login: {:?}", e);
+                }
+            }
+        });
+    }
+}
+
+/// Error 429 con la cabecera `Retry-After` (en segundos).
+pub fn too_many_attempts(retry_after: Duration) -> ApiError {
+    let seconds = (retry_after.num_milliseconds() + 999) / 1000;
+    ApiError::TooManyRequests {
+        retry_after: std::time::Duration::from_secs(seconds.max(1) as u64),
+    }
+}
+
+/// IP de origen de la petición. Con `TRUST_FORWARDED_FOR=true` (detrás de un
+/// proxy de confianza) se toma la primera dirección de `X-Forwarded-For`.
+pub struct ClientIp(pub String);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if state.login_guard.trust_forwarded_for {
+            let forwarded = parts.headers
+                .get("x-forwarded-for")
+                .and_then(|h| h.to_str().ok())
+                .and_then(|h| h.split(',').next())
+                .map(str::trim)
+                .filter(|ip| !ip.is_empty());
+            if let Some(ip) = forwarded {
+                return Ok(Self(ip.to_string()));
+            }
+        }
+
+        parts.extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| Self(addr.ip().to_string()))
+            .ok_or_else(|| {
+                tracing::error!("Falta ConnectInfo: el servidor no se inició con `into_make_service_with_connect_info`");
+                ApiError::Internal
+            })
+    }
+}
+
+/// Un bloqueo vigente, por cuenta o por IP.
+#[derive(Serialize, ToSchema)]
+pub struct Lockout {
+    /// `account` (email) o `ip`.
+    #[schema(example = "account")]
+    scope: String,
+    #[schema(example = "test@example.com")]
+    key: String,
+    failed_attempts: i32,
+    last_failed_at: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+/// Identifica un bloqueo en la ruta del endpoint para eliminarlo.
+#[derive(Deserialize)]
+pub struct LockoutPath {
+    scope: String,
+    key: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/lockouts",
+    responses(
+        (status = 200, description = "Bloqueos de login vigentes", body = [Lockout]),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para listar los bloqueos de login vigentes (solo administradores)
+pub async fn list_lockouts(
+    State(state): State<AppState>,
+    _admin: RequireRole<Admin>,
+) -> Result<impl IntoResponse, ApiError> {
+    let lockouts = sqlx::query_as!(
+        Lockout,
+        "SELECT scope, key, failed_attempts, last_failed_at, locked_until FROM login_failures
+         WHERE locked_until > NOW()
+         ORDER BY locked_until DESC"
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(lockouts))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/lockouts/{scope}/{key}",
+    params(
+        ("scope" = String, Path, description = "`account` o `ip`"),
+        ("key" = String, Path, description = "Email de la cuenta o dirección IP")
+    ),
+    responses(
+        (status = 204, description = "Bloqueo y contador de intentos eliminados"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No hay intentos fallidos registrados para esa clave", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para levantar un bloqueo de login (solo administradores)
+pub async fn clear_lockout(
+    State(state): State<AppState>,
+    _admin: RequireRole<Admin>,
+    Path(path): Path<LockoutPath>,
+) -> Result<impl IntoResponse, ApiError> {
+    // Las cuentas se registran con el email en minúsculas.
+    let key = match path.scope.as_str() {
+        "account" => path.key.to_lowercase(),
+        _ => path.key,
+    };
+
+    let result = sqlx::query!(
+        "DELETE FROM login_failures WHERE scope = $1 AND key = $2",
+        path.scope,
+        key
+    )
+    .execute(&state.db_pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("No hay intentos fallidos registrados para esa clave".into()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_doubles_with_each_failure() {
+        let delays: Vec<i64> = (1..=6).map(|n| LoginGuard::delay(n).num_seconds()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32]);
+    }
+
+    #[test]
+    fn delay_is_capped() {
+        assert_eq!(LoginGuard::delay(7).num_seconds(), MAX_DELAY_SECONDS);
+        assert_eq!(LoginGuard::delay(20).num_seconds(), MAX_DELAY_SECONDS);
+        assert_eq!(LoginGuard::delay(i32::MAX).num_seconds(), MAX_DELAY_SECONDS);
+    }
+
+    #[test]
+    fn delay_without_failures_is_the_minimum() {
+        assert_eq!(LoginGuard::delay(0).num_seconds(), 1);
+        assert_eq!(LoginGuard::delay(-3).num_seconds(), 1);
+    }
+}
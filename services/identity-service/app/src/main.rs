@@ -1,32 +1,45 @@
 use axum::{
     extract::State,
     http::StatusCode,
-    response::{IntoResponse, Response},
-    routing::{get, post},
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use argon2::{self, Config};
-use chrono::{Duration, Utc};
-use dotenvy::dotenv;
+use chrono::Duration;
 use rand::Rng;
-use jsonwebtoken::{encode, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use std::env;
 use std::net::SocketAddr;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use std::path::PathBuf;
+use std::sync::Arc;
 use utoipa::{OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 use uuid::Uuid;
+use validator::Validate;
 
-// (NUEVO) Enum para los roles de usuario, debe coincidir con el tipo SQL
-#[derive(Debug, Serialize, Deserialize, sqlx::Type, ToSchema, Clone, Copy)]
-#[sqlx(type_name = "user_role", rename_all = "lowercase")]
-pub enum Role {
-    Student,
-    Instructor,
-    Admin,
-}
+use lms_common::config::{self, env_or};
+use lms_common::validation::{self, ValidatedJson};
+use lms_common::error::{self, ApiError};
+use lms_common::mail::{self, MailSender};
+use lms_common::{ProblemDetails, Role};
+
+mod admin;
+mod auth;
+mod keys;
+mod lockout;
+mod mfa;
+mod password;
+mod profile;
+mod tokens;
+mod verification;
+
+use keys::SigningKeys;
+use lockout::{ClientIp, LoginGuard};
+use mfa::MfaPolicy;
+use verification::EmailVerificationPolicy;
 
 // --- Estructuras de Datos y Schemas para OpenAPI ---
 
@@ -40,6 +53,8 @@ struct User {
     email: String,
     role: Role, // (NUEVO)
     created_at: chrono::DateTime<chrono::Utc>,
+    email_verified_at: Option<chrono::DateTime<chrono::Utc>>,
+    deactivated_at: Option<chrono::DateTime<chrono::Utc>>,
     // El hash de la contraseña nunca debe ser expuesto en una respuesta de API.
     // Lo mantenemos aquí porque `query_as!` lo necesita para mapear el resultado.
     #[serde(skip_serializing)]
@@ -47,17 +62,22 @@ struct User {
 }
 
 /// Datos para crear un nuevo usuario (payload de registro).
-#[derive(Deserialize, ToSchema)]
+#[derive(Deserialize, ToSchema, Validate)]
 struct CreateUser {
-    #[schema(example = "Juan")]
+    #[schema(example = "Juan", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
     first_name: String,
-    #[schema(example = "Allende")]
+    #[schema(example = "Allende", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
     last_name: String,
-    #[schema(example = "nurfog")]
+    #[schema(example = "nurfog", min_length = 3, max_length = 50)]
+    #[validate(length(min = 3, max = 50, message = "Debe tener entre 3 y 50 caracteres"), custom(function = "validation::username_chars"))]
     username: String,
-    #[schema(example = "test@example.com")]
+    #[schema(example = "test@example.com", max_length = 255)]
+    #[validate(email(message = "No es un email válido"), length(max = 255, message = "Debe tener como máximo 255 caracteres"))]
     email: String,
-    #[schema(example = "SecurePassword123")]
+    #[schema(example = "SecurePassword123", min_length = 8, max_length = 128)]
+    #[validate(length(min = 8, max = 128, message = "Debe tener entre 8 y 128 caracteres"), custom(function = "validation::password_strength"))]
     password: String,
 }
 
@@ -73,27 +93,51 @@ struct UserResponse {
 }
 
 /// (NUEVO) Payload para el endpoint de login.
-#[derive(Deserialize, ToSchema)]
+#[derive(Deserialize, ToSchema, Validate)]
 struct LoginPayload {
-    // El login puede ser con email o username, aquí usamos email.
-    #[schema(example = "test@example.com")]
-    email: String,
-    #[schema(example = "SecurePassword123")]
+    /// Email o username, sin distinguir mayúsculas. Se acepta también como `email`.
+    #[schema(example = "test@example.com", min_length = 1, max_length = 255)]
+    #[serde(alias = "email")]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"))]
+    identifier: String,
+    // Sin reglas de complejidad (pueden existir contraseñas anteriores a ellas),
+    // pero con máximo para no hashear entradas enormes.
+    #[schema(example = "SecurePassword123", min_length = 1, max_length = 128)]
+    #[validate(length(min = 1, max = 128, message = "Debe tener entre 1 y 128 caracteres"))]
     password: String,
 }
 
 /// (NUEVO) Respuesta del endpoint de login, contiene el token.
 #[derive(Serialize, ToSchema)]
 struct TokenResponse {
+    /// Access token JWT de corta duración.
     token: String,
+    /// Refresh token opaco para obtener un nuevo access token en `/api/v1/auth/refresh`.
+    refresh_token: String,
+    #[schema(example = "Bearer")]
+    token_type: &'static str,
+    /// Segundos de validez restantes del access token.
+    #[schema(example = 900)]
+    expires_in: i64,
 }
 
-/// (NUEVO) Estructura de los claims (contenido) del JWT.
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: Uuid, // Subject (el ID del usuario)
-    role: Role, // (NUEVO) Rol del usuario
-    exp: i64,  // Expiration time
+/// Respuesta del login: los tokens, o un desafío si falta el segundo factor.
+#[derive(Serialize, ToSchema)]
+#[serde(untagged)]
+enum LoginResponse {
+    Tokens(TokenResponse),
+    MfaChallenge(mfa::MfaChallengeResponse),
+}
+
+impl TokenResponse {
+    fn new(token: String, refresh_token: String, state: &AppState) -> Self {
+        Self {
+            token,
+            refresh_token,
+            token_type: "Bearer",
+            expires_in: state.access_token_ttl.num_seconds(),
+        }
+    }
 }
 
 // --- Estado de la Aplicación ---
@@ -101,7 +145,16 @@ struct Claims {
 #[derive(Clone)]
 struct AppState {
     db_pool: PgPool,
-    jwt_secret: String, // (NUEVO) Secreto para firmar los JWT
+    keys: Arc<SigningKeys>, // Claves Ed25519 para firmar y verificar los JWT
+    access_token_ttl: Duration,
+    refresh_token_ttl: Duration,
+    password_reset_ttl: Duration,
+    email_verification_ttl: Duration,
+    email_verification_policy: EmailVerificationPolicy,
+    mfa_policy: Arc<MfaPolicy>,
+    login_guard: Arc<LoginGuard>,
+    mailer: Arc<dyn MailSender>,
+    app_base_url: String, // URL pública del portal, usada en los enlaces enviados por correo
 }
 
 // --- Documentación de la API (OpenAPI) ---
@@ -110,11 +163,46 @@ struct AppState {
 #[openapi(
     paths(
         health_check,
+        keys::jwks,
         register,
-        login
+        login,
+        tokens::refresh,
+        tokens::logout,
+        tokens::revoke_user_sessions,
+        profile::get_me,
+        profile::update_me,
+        profile::request_email_change,
+        profile::confirm_email_change,
+        profile::change_password,
+        admin::list_users,
+        admin::get_user,
+        admin::change_role,
+        admin::list_role_changes,
+        admin::deactivate_user,
+        admin::reactivate_user,
+        admin::delete_user,
+        lockout::list_lockouts,
+        lockout::clear_lockout,
+        password::forgot_password,
+        password::reset_password,
+        verification::verify_email,
+        verification::resend_verification,
+        mfa::verify,
+        mfa::setup,
+        mfa::enable,
+        mfa::disable,
+        mfa::regenerate_recovery_codes
     ),
     components(
-        schemas(User, CreateUser, UserResponse, LoginPayload, TokenResponse)
+        schemas(User, CreateUser, UserResponse, LoginPayload, TokenResponse, tokens::RefreshPayload, tokens::LogoutPayload,
+            password::ForgotPasswordPayload, password::ResetPasswordPayload,
+            verification::VerifyEmailPayload, verification::ResendVerificationPayload,
+            LoginResponse, mfa::MfaChallengeResponse, mfa::MfaVerifyPayload, mfa::MfaCodePayload,
+            mfa::MfaSetupResponse, mfa::MfaEnabledResponse, mfa::RecoveryCodesResponse,
+            ProblemDetails, validation::FieldError,
+            profile::ProfileResponse, profile::UpdateProfilePayload, profile::ChangeEmailPayload,
+            profile::ConfirmEmailChangePayload, profile::ChangePasswordPayload,
+            lockout::Lockout, admin::AdminUserResponse, admin::UserPage, admin::ChangeRolePayload, admin::RoleChange)
     ),
     tags(
         (name = "Identity Service", description = "API para gestión de usuarios y autenticación")
@@ -126,17 +214,21 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() {
-    eprintln!("Starting identity service");
-    dotenv().ok();
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL debe estar configurada");
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET debe estar configurado"); // (NUEVO)
+    config::init("identity-service");
+
+    let database_url = config::required("DATABASE_URL");
+    let keys_dir: PathBuf = env_or("JWT_KEYS_DIR", "keys".into());
+    let active_kid = env::var("JWT_ACTIVE_KID").ok();
+    let keys = Arc::new(SigningKeys::load_or_generate(&keys_dir, active_kid.as_deref()));
+    let access_token_ttl = Duration::minutes(env_or("ACCESS_TOKEN_TTL_MINUTES", 15));
+    let refresh_token_ttl = Duration::days(env_or("REFRESH_TOKEN_TTL_DAYS", 30));
+    let password_reset_ttl = Duration::minutes(env_or("PASSWORD_RESET_TTL_MINUTES", 60));
+    let email_verification_ttl = Duration::hours(env_or("EMAIL_VERIFICATION_TTL_HOURS", 48));
+    let email_verification_policy = EmailVerificationPolicy::from_env();
+    let mfa_policy = Arc::new(MfaPolicy::from_env());
+    let login_guard = Arc::new(LoginGuard::from_env());
+    let app_base_url = env_or("APP_BASE_URL", "http://localhost:8080".to_string());
+    let mailer = mail::from_env();
 
     let db_pool = PgPoolOptions::new()
         .max_connections(5)
@@ -147,20 +239,75 @@ async fn main() {
     // Ejecutar migraciones
     sqlx::migrate!("./migrations").run(&db_pool).await.expect("Failed to run migrations");
 
-    let app_state = AppState { db_pool, jwt_secret }; // (NUEVO)
+    login_guard.clone().spawn_cleanup_task(db_pool.clone());
+    tokens::spawn_revocation_cleanup_task(db_pool.clone());
+
+    let app_state = AppState {
+        db_pool,
+        keys,
+        access_token_ttl,
+        refresh_token_ttl,
+        password_reset_ttl,
+        email_verification_ttl,
+        email_verification_policy,
+        mfa_policy,
+        login_guard,
+        mailer,
+        app_base_url,
+    };
 
     let app = Router::new()
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .route("/health", get(health_check))
+        .route("/.well-known/jwks.json", get(keys::jwks))
         .route("/api/v1/auth/register", post(register))
         .route("/api/v1/auth/login", post(login)) // (NUEVO)
+        .route("/api/v1/auth/refresh", post(tokens::refresh))
+        .route("/api/v1/auth/logout", post(tokens::logout))
+        .route("/api/v1/auth/password/forgot", post(password::forgot_password))
+        .route("/api/v1/auth/password/reset", post(password::reset_password))
+        .route("/api/v1/auth/email/verify", post(verification::verify_email))
+        .route("/api/v1/auth/email/resend", post(verification::resend_verification))
+        .route("/api/v1/auth/mfa/verify", post(mfa::verify))
+        .route("/api/v1/auth/mfa/setup", post(mfa::setup))
+        .route("/api/v1/auth/mfa/enable", post(mfa::enable))
+        .route("/api/v1/auth/mfa/disable", post(mfa::disable))
+        .route("/api/v1/auth/mfa/recovery-codes", post(mfa::regenerate_recovery_codes))
+        .route("/api/v1/auth/email/change/confirm", post(profile::confirm_email_change))
+        .route("/api/v1/users/me", get(profile::get_me).patch(profile::update_me))
+        .route("/api/v1/users/me/email", post(profile::request_email_change))
+        .route("/api/v1/users/me/password", post(profile::change_password))
+        .route("/api/v1/admin/users", get(admin::list_users))
+        .route("/api/v1/admin/users/{id}", get(admin::get_user).delete(admin::delete_user))
+        .route("/api/v1/admin/users/{id}/role", put(admin::change_role))
+        .route("/api/v1/admin/users/{id}/role-changes", get(admin::list_role_changes))
+        .route("/api/v1/admin/users/{id}/deactivate", post(admin::deactivate_user))
+        .route("/api/v1/admin/users/{id}/reactivate", post(admin::reactivate_user))
+        .route("/api/v1/admin/users/{id}/sessions/revoke", post(tokens::revoke_user_sessions))
+        .route("/api/v1/admin/lockouts", get(lockout::list_lockouts))
+        .route("/api/v1/admin/lockouts/{scope}/{key}", delete(lockout::clear_lockout))
+        .layer(middleware::from_fn(error::problem_details))
         .with_state(app_state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
     tracing::info!("Escuchando en {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // `ConnectInfo` da la IP de origen usada por el bloqueo de login
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
+}
+
+/// Hashea una contraseña con Argon2 y un salt aleatorio.
+fn hash_password(password: &str) -> Result<String, ApiError> {
+    // Usamos una configuración segura por defecto y un salt aleatorio.
+    let salt = rand::thread_rng().r#gen::<[u8; 32]>();
+    let config = Config::default();
+    argon2::hash_encoded(password.as_bytes(), &salt, &config).map_err(|e| {
+        tracing::error!("Error al hashear la contraseña: {:?}", e);
+        ApiError::Internal
+    })
 }
 
 #[utoipa::path(
@@ -180,26 +327,22 @@ async fn health_check() -> &'static str {
     request_body = CreateUser,
     responses(
         (status = 201, description = "Usuario creado exitosamente", body = UserResponse),
-        (status = 409, description = "El email ya está en uso"),
-        (status = 500, description = "Error interno del servidor")
+        (status = 409, description = "El email o el username ya están en uso", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
 async fn register(
     State(state): State<AppState>,
-    Json(payload): Json<CreateUser>,
-) -> Response {
+    ValidatedJson(payload): ValidatedJson<CreateUser>,
+) -> Result<impl IntoResponse, ApiError> {
     // Hashing de contraseña con rust-argon2
-    // Usamos una configuración segura por defecto y un salt aleatorio.
-    let salt = rand::thread_rng().r#gen::<[u8; 32]>();
-    let config = Config::default();
-    let password_hash = match argon2::hash_encoded(payload.password.as_bytes(), &salt, &config) {
-        Ok(h) => h,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
+    let password_hash = hash_password(&payload.password)?;
 
-    let new_user_result = sqlx::query_as!(
+    // 409 si el email o el username ya están en uso
+    let user = sqlx::query_as!(
         User,
-        "INSERT INTO users (first_name, last_name, username, email, password_hash) VALUES ($1, $2, $3, $4, $5) RETURNING id, first_name, last_name, username, email, password_hash, role as \"role: _\", created_at as \"created_at!\"",
+        "INSERT INTO users (first_name, last_name, username, email, password_hash) VALUES ($1, $2, $3, $4, $5) RETURNING id, first_name, last_name, username, email, password_hash, role as \"role: _\", created_at as \"created_at!\", email_verified_at, deactivated_at",
         payload.first_name,
         payload.last_name,
         payload.username,
@@ -207,25 +350,19 @@ async fn register(
         password_hash
     )
     .fetch_one(&state.db_pool)
-    .await
-    ;
-    match new_user_result {
-        Ok(user) => { // La variable `user` ahora es del tipo `User`
-            let user_response = UserResponse {
-                id: user.id,
-                email: user.email,
-                first_name: user.first_name,
-                last_name: user.last_name,
-                username: user.username,
-                role: user.role,
-            };
-            (StatusCode::CREATED, Json(user_response)).into_response()
-        }
-        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
-            StatusCode::CONFLICT.into_response()
-        }
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    }
+    .await?;
+
+    verification::send_verification_email(&state, user.id, &user.email);
+
+    let user_response = UserResponse {
+        id: user.id,
+        email: user.email,
+        first_name: user.first_name,
+        last_name: user.last_name,
+        username: user.username,
+        role: user.role,
+    };
+    Ok((StatusCode::CREATED, Json(user_response)))
 }
 
 #[utoipa::path(
@@ -233,53 +370,89 @@ async fn register(
     path = "/api/v1/auth/login",
     request_body = LoginPayload,
     responses(
-        (status = 200, description = "Login exitoso, devuelve token JWT", body = TokenResponse),
-        (status = 401, description = "Credenciales inválidas"),
-        (status = 500, description = "Error interno del servidor")
+        (status = 200, description = "Login exitoso: devuelve access token JWT y refresh token, o un `mfa_token` si falta el segundo factor", body = LoginResponse),
+        (status = 401, description = "Credenciales inválidas", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Cuenta desactivada, o email sin verificar (según `EMAIL_VERIFICATION_POLICY`)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Demasiados intentos fallidos; la cabecera `Retry-After` indica cuándo reintentar", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
 /// Handler para el login de usuarios
 async fn login(
     State(state): State<AppState>,
-    Json(payload): Json<LoginPayload>,
-    ) -> Response {
-    // 1. Buscar al usuario por email
-    let user = match sqlx::query_as!(
+    ClientIp(ip): ClientIp,
+    ValidatedJson(payload): ValidatedJson<LoginPayload>,
+    ) -> Result<impl IntoResponse, ApiError> {
+    let identifier = payload.identifier.trim().to_lowercase();
+
+    // 1. Buscar al usuario por email o username (sin distinguir mayúsculas).
+    //    Si el identificador coincide con el email de uno y el username de otro, gana el email.
+    let user = sqlx::query_as!(
         User,
-        "SELECT id, first_name, last_name, username, email, password_hash, role as \"role: _\", created_at as \"created_at!\" FROM users WHERE email = $1",
-        payload.email.to_lowercase(),
+        "SELECT id, first_name, last_name, username, email, password_hash, role as \"role: _\", created_at as \"created_at!\", email_verified_at, deactivated_at
+         FROM users WHERE email = $1 OR LOWER(username) = $1
+         ORDER BY (email = $1) DESC
+         LIMIT 1",
+        identifier,
     )
     .fetch_optional(&state.db_pool)
-    .await
-    {
-        Ok(Some(user)) => user,
-        Ok(None) => return StatusCode::UNAUTHORIZED.into_response(), // Usuario no encontrado
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
+    .await?;
 
-    // 2. Verificar la contraseña
-    let password_valid = argon2::verify_encoded(&user.password_hash, payload.password.as_bytes()).unwrap_or(false);
+    // Los intentos se cuentan por el email de la cuenta, entre por email o por
+    // username; si no existe, por el identificador enviado.
+    let account = user.as_ref().map_or_else(|| identifier.clone(), |u| u.email.clone());
 
-    if !password_valid {
-        return StatusCode::UNAUTHORIZED.into_response(); // Contraseña incorrecta
+    // Rechazar sin verificar nada si la cuenta o la IP están bloqueadas
+    if let Some(wait) = state.login_guard.retry_after(&state.db_pool, Some(&account), &ip).await? {
+        return Err(lockout::too_many_attempts(wait));
     }
 
-    // 3. Crear el token JWT
-    let claims = Claims {
-        sub: user.id,
-        role: user.role,
-        exp: (Utc::now() + Duration::hours(24)).timestamp(), // Expira en 24 horas
+    // 2. Verificar la contraseña. Si el usuario no existe se verifica igual contra
+    //    un hash de referencia, para no revelar por el tiempo de respuesta qué cuentas existen.
+    let password_valid = match &user {
+        Some(user) => argon2::verify_encoded(&user.password_hash, payload.password.as_bytes()).unwrap_or(false),
+        None => {
+            state.login_guard.verify_dummy(&payload.password);
+            false
+        }
     };
 
-    let token = match encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(state.jwt_secret.as_ref()),
-    ) {
-        Ok(t) => t,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    let Some(user) = user.filter(|_| password_valid) else {
+        if let Err(e) = state.login_guard.record_failure(&state.db_pool, Some(&account), &ip).await {
+            tracing::error!("Error al registrar intento fallido: {:?}", e);
+        }
+        return Err(ApiError::Status(
+            StatusCode::UNAUTHORIZED,
+            Some("Usuario o contraseña incorrectos".into()),
+        ));
     };
 
-    // 4. Devolver el token
-    (StatusCode::OK, Json(TokenResponse { token })).into_response()
+    // 3. Rechazar cuentas desactivadas por un administrador
+    if user.deactivated_at.is_some() {
+        return Err(ApiError::Forbidden("La cuenta está desactivada".into()));
+    }
+
+    // 4. Exigir email verificado si la política lo pide
+    let email_verified = user.email_verified_at.is_some();
+    if !email_verified && state.email_verification_policy == EmailVerificationPolicy::Login {
+        return Err(ApiError::Forbidden("Debes verificar tu email antes de iniciar sesión".into()));
+    }
+
+    // 5. Pedir el segundo factor si el usuario tiene MFA (o su rol lo exige)
+    //    El contador de fallos de la cuenta no se limpia hasta completar el segundo
+    //    factor: los códigos incorrectos siguen sumando aunque se repita el login.
+    if let Some(challenge) = mfa::login_challenge(&state, user.id, user.role).await? {
+        return Ok(Json(LoginResponse::MfaChallenge(challenge)));
+    }
+
+    if let Err(e) = state.login_guard.clear_account(&state.db_pool, &account).await {
+        tracing::error!("Error al limpiar intentos fallidos: {:?}", e);
+    }
+
+    // 6. Crear el access token JWT y el refresh token (nueva familia)
+    let tokens = tokens::issue_token_pair(&state, user.id, user.role, email_verified).await?;
+
+    // 7. Devolver los tokens
+    Ok(Json(LoginResponse::Tokens(tokens)))
 }
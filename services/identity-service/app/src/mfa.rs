@@ -0,0 +1,651 @@
+// --- Autenticación de dos factores (TOTP, RFC 6238) ---
+//
+// Flujo de login con MFA:
+// 1. `login` valida la contraseña y, si el usuario tiene MFA activo, responde con
+//    un `mfa_token` de corta duración (audiencia `mfa-pending`) en lugar de tokens.
+// 2. `POST /auth/mfa/verify` canjea ese `mfa_token` más un código TOTP (o un
+//    código de recuperación) por el par access/refresh token. El `mfa_token` sirve
+//    una sola vez y se invalida tras `MAX_CHALLENGE_ATTEMPTS` códigos incorrectos;
+//    además, cada código incorrecto cuenta para el bloqueo de la cuenta y de la IP
+//    igual que una contraseña incorrecta.
+//
+// Si el rol del usuario está en `MFA_REQUIRED_ROLES` y aún no configuró MFA,
+// el `mfa_token` indica `enrollment_required` y sirve como credencial para
+// `setup` y `enable`; al activarlo se completa el login.
+
+use std::env;
+
+use axum::{
+    extract::{FromRequestParts, State},
+    http::{request::Parts, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Duration, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use totp_rs::{Builder, Secret, Totp};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use lms_common::auth::{bearer_token, AuthState};
+use lms_common::{ApiError, Claims, ProblemDetails, Role};
+
+use crate::lockout::{self, ClientIp};
+use lms_common::tokens::hash_token;
+use crate::tokens;
+use crate::{AppState, TokenResponse};
+
+const PENDING_AUDIENCE: &str = "mfa-pending";
+const PENDING_TTL_MINUTES: i64 = 5;
+const RECOVERY_CODE_COUNT: usize = 10;
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+/// Configuración de MFA leída del entorno.
+pub struct MfaPolicy {
+    required_roles: Vec<Role>,
+    issuer: String,
+}
+
+impl MfaPolicy {
+    pub fn from_env() -> Self {
+        let required_roles = env::var("MFA_REQUIRED_ROLES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(|r| {
+                r.parse::<Role>()
+                    .unwrap_or_else(|e| panic!("MFA_REQUIRED_ROLES inválido: {e}"))
+            })
+            .collect();
+
+        Self {
+            required_roles,
+            issuer: env::var("MFA_ISSUER").unwrap_or_else(|_| "LMS".into()),
+        }
+    }
+
+    fn requires(&self, role: Role) -> bool {
+        self.required_roles.contains(&role)
+    }
+}
+
+/// Claims del token intermedio entre la contraseña y el segundo factor.
+#[derive(Serialize, Deserialize)]
+struct PendingClaims {
+    sub: Uuid,
+    aud: String,
+    exp: i64,
+    jti: Uuid,
+    enrollment_required: bool,
+}
+
+/// Respuesta de login cuando todavía falta el segundo factor.
+#[derive(Serialize, ToSchema)]
+pub struct MfaChallengeResponse {
+    #[schema(example = true)]
+    mfa_required: bool,
+    /// `true` si el rol exige MFA y el usuario aún debe configurarlo con `/auth/mfa/setup`.
+    enrollment_required: bool,
+    /// Token de corta duración para `/auth/mfa/verify` (o para `setup`/`enable` al enrolarse).
+    mfa_token: String,
+    #[schema(example = 300)]
+    expires_in: i64,
+}
+
+/// Payload para completar el login con el segundo factor.
+#[derive(Deserialize, ToSchema)]
+pub struct MfaVerifyPayload {
+    mfa_token: String,
+    /// Código TOTP de 6 dígitos o un código de recuperación.
+    #[schema(example = "123456")]
+    code: String,
+}
+
+/// Payload con un código TOTP (o de recuperación, donde se acepte).
+#[derive(Deserialize, ToSchema)]
+pub struct MfaCodePayload {
+    #[schema(example = "123456")]
+    code: String,
+}
+
+/// Secreto TOTP recién generado, pendiente de confirmar con `/auth/mfa/enable`.
+#[derive(Serialize, ToSchema)]
+pub struct MfaSetupResponse {
+    /// Secreto en base32, para ingresarlo manualmente en la app autenticadora.
+    secret: String,
+    /// URI `otpauth://` que la app autenticadora lee desde un código QR.
+    #[schema(example = "otpauth://totp/LMS:test%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=LMS")]
+    provisioning_uri: String,
+}
+
+/// Resultado de activar MFA.
+#[derive(Serialize, ToSchema)]
+pub struct MfaEnabledResponse {
+    /// Códigos de recuperación de un solo uso. Solo se muestran esta vez.
+    recovery_codes: Vec<String>,
+    /// Tokens de sesión, presentes si la activación completó un login pendiente.
+    tokens: Option<TokenResponse>,
+}
+
+/// Códigos de recuperación regenerados.
+#[derive(Serialize, ToSchema)]
+pub struct RecoveryCodesResponse {
+    recovery_codes: Vec<String>,
+}
+
+/// Usuario autenticado para configurar MFA: con un access token normal o con
+/// un `mfa_token` de login pendiente que exige enrolamiento.
+pub struct MfaSubject {
+    user_id: Uuid,
+    pending_login: bool,
+}
+
+impl FromRequestParts<AppState> for MfaSubject {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts).ok_or(ApiError::Unauthorized)?;
+
+        match state.verify_access_token(token).await {
+            Ok(claims) => return Ok(Self { user_id: claims.sub, pending_login: false }),
+            Err(StatusCode::UNAUTHORIZED) => {}
+            Err(status) => return Err(ApiError::Status(status, None)),
+        }
+
+        match state.keys.verify::<PendingClaims>(token, Some(PENDING_AUDIENCE)) {
+            Some(pending) if pending.enrollment_required => Ok(Self {
+                user_id: pending.sub,
+                pending_login: true,
+            }),
+            _ => Err(ApiError::Unauthorized),
+        }
+    }
+}
+
+/// Decide si el login necesita un segundo factor. Devuelve el desafío a enviar
+/// al cliente, o `None` si se pueden emitir los tokens directamente.
+pub async fn login_challenge(
+    state: &AppState,
+    user_id: Uuid,
+    role: Role,
+) -> Result<Option<MfaChallengeResponse>, ApiError> {
+    let enabled = sqlx::query_scalar!(
+        "SELECT EXISTS (SELECT 1 FROM user_mfa WHERE user_id = $1 AND enabled_at IS NOT NULL) AS \"enabled!\"",
+        user_id
+    )
+    .fetch_one(&state.db_pool)
+    .await?;
+
+    let enrollment_required = !enabled && state.mfa_policy.requires(role);
+    if !enabled && !enrollment_required {
+        return Ok(None);
+    }
+
+    let expires_at = Utc::now() + Duration::minutes(PENDING_TTL_MINUTES);
+    let claims = PendingClaims {
+        sub: user_id,
+        aud: PENDING_AUDIENCE.to_string(),
+        exp: expires_at.timestamp(),
+        jti: Uuid::new_v4(),
+        enrollment_required,
+    };
+
+    // Un nuevo login reemplaza el desafío anterior del usuario.
+    if !enrollment_required {
+        sqlx::query!(
+            "INSERT INTO mfa_login_challenges (user_id, jti, expires_at) VALUES ($1, $2, $3)
+             ON CONFLICT (user_id) DO UPDATE
+             SET jti = EXCLUDED.jti, failed_attempts = 0, expires_at = EXCLUDED.expires_at, used_at = NULL",
+            user_id,
+            claims.jti,
+            expires_at
+        )
+        .execute(&state.db_pool)
+        .await?;
+    }
+
+    let mfa_token = state.keys.sign(&claims).map_err(|e| {
+        tracing::error!("Error al firmar mfa_token: {:?}", e);
+        ApiError::Internal
+    })?;
+
+    Ok(Some(MfaChallengeResponse {
+        mfa_required: true,
+        enrollment_required,
+        mfa_token,
+        expires_in: PENDING_TTL_MINUTES * 60,
+    }))
+}
+
+fn build_totp(secret: Secret, issuer: &str, account_name: &str) -> Option<Totp> {
+    Builder::new()
+        .with_secret(secret)
+        .with_issuer(Some(issuer))
+        .with_account_name(account_name)
+        .build()
+        .ok()
+}
+
+/// Valida un código TOTP contra el secreto guardado y lo marca como usado.
+/// Un código ya aceptado (o uno de un intervalo anterior) se rechaza.
+async fn accept_totp_code(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    secret: &str,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let Some(step) = Secret::try_from_base32(secret)
+        .ok()
+        .and_then(|s| build_totp(s, "", ""))
+        .and_then(|totp| totp.check_current(code.trim()))
+    else {
+        return Ok(false);
+    };
+
+    let updated = sqlx::query!(
+        "UPDATE user_mfa SET last_used_step = $2
+         WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)",
+        user_id,
+        step as i64
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(updated.rows_affected() == 1)
+}
+
+/// Normaliza un código de recuperación ("ABCDE-12345" y "abcde12345" son el mismo).
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Consume un código de recuperación si es válido y no fue usado.
+async fn accept_recovery_code(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query!(
+        "UPDATE mfa_recovery_codes SET used_at = NOW()
+         WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL",
+        user_id,
+        hash_token(&normalize_recovery_code(code))
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(updated.rows_affected() == 1)
+}
+
+/// Verifica el segundo factor de un usuario con MFA activo: código TOTP o,
+/// si `allow_recovery`, un código de recuperación.
+async fn accept_second_factor(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    code: &str,
+    allow_recovery: bool,
+) -> Result<bool, sqlx::Error> {
+    let secret = sqlx::query_scalar!(
+        "SELECT totp_secret FROM user_mfa WHERE user_id = $1 AND enabled_at IS NOT NULL",
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(secret) = secret else {
+        return Ok(false);
+    };
+
+    if accept_totp_code(conn, user_id, &secret, code).await? {
+        return Ok(true);
+    }
+
+    Ok(allow_recovery && accept_recovery_code(conn, user_id, code).await?)
+}
+
+/// Reemplaza los códigos de recuperación del usuario y devuelve los nuevos en claro.
+async fn replace_recovery_codes(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query!("DELETE FROM mfa_recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw = hex::encode(rand::thread_rng().r#gen::<[u8; 5]>());
+            format!("{}-{}", &raw[..5], &raw[5..])
+        })
+        .collect();
+
+    for code in &codes {
+        sqlx::query!(
+            "INSERT INTO mfa_recovery_codes (user_id, code_hash) VALUES ($1, $2)",
+            user_id,
+            hash_token(&normalize_recovery_code(code))
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(codes)
+}
+
+/// Datos del usuario necesarios para emitir sus tokens; `None` si la cuenta no
+/// existe o fue desactivada mientras el login estaba pendiente del segundo factor.
+async fn token_subject(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<Option<(Role, bool)>, sqlx::Error> {
+    let user = sqlx::query!(
+        "SELECT role as \"role: Role\", email_verified_at FROM users WHERE id = $1 AND deactivated_at IS NULL",
+        user_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(user.map(|u| (u.role, u.email_verified_at.is_some())))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/verify",
+    request_body = MfaVerifyPayload,
+    responses(
+        (status = 200, description = "Segundo factor válido, devuelve access token JWT y refresh token", body = TokenResponse),
+        (status = 401, description = "mfa_token inválido, expirado, ya usado o invalidado por demasiados códigos incorrectos, o código incorrecto", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "La cuenta se desactivó después del primer paso del login", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Demasiados intentos fallidos para la cuenta o desde esta IP", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+/// Handler para completar un login pendiente de segundo factor
+pub async fn verify(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(payload): Json<MfaVerifyPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let expired = || {
+        ApiError::Status(
+            StatusCode::UNAUTHORIZED,
+            Some("El mfa_token no es válido, expiró o ya no admite más intentos; inicia sesión de nuevo".into()),
+        )
+    };
+
+    let pending = state
+        .keys
+        .verify::<PendingClaims>(&payload.mfa_token, Some(PENDING_AUDIENCE))
+        .filter(|p| !p.enrollment_required)
+        .ok_or_else(expired)?;
+
+    // Los códigos fallidos cuentan para el bloqueo de la cuenta y de la IP del login.
+    let account = sqlx::query_scalar!("SELECT email FROM users WHERE id = $1", pending.sub)
+        .fetch_optional(&state.db_pool)
+        .await?
+        .ok_or_else(expired)?;
+    if let Some(wait) = state.login_guard.retry_after(&state.db_pool, Some(&account), &ip).await? {
+        return Err(lockout::too_many_attempts(wait));
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+
+    // `FOR UPDATE` evita canjear el mismo desafío dos veces en paralelo.
+    let challenge = sqlx::query_scalar!(
+        "SELECT failed_attempts FROM mfa_login_challenges
+         WHERE user_id = $1 AND jti = $2 AND used_at IS NULL AND expires_at > NOW() AND failed_attempts < $3
+         FOR UPDATE",
+        pending.sub,
+        pending.jti,
+        MAX_CHALLENGE_ATTEMPTS
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    if challenge.is_none() {
+        return Err(expired());
+    }
+
+    if !accept_second_factor(&mut tx, pending.sub, &payload.code, true).await? {
+        sqlx::query!(
+            "UPDATE mfa_login_challenges SET failed_attempts = failed_attempts + 1 WHERE user_id = $1",
+            pending.sub
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if let Err(e) = state.login_guard.record_failure(&state.db_pool, Some(&account), &ip).await {
+            tracing::error!("Error al registrar intento fallido: {:?}", e);
+        }
+        return Err(ApiError::Status(
+            StatusCode::UNAUTHORIZED,
+            Some("El código no es correcto".into()),
+        ));
+    }
+
+    let (role, email_verified) = token_subject(&mut tx, pending.sub)
+        .await?
+        .ok_or_else(|| ApiError::Forbidden("La cuenta está desactivada".into()))?;
+    sqlx::query!(
+        "UPDATE mfa_login_challenges SET used_at = NOW() WHERE user_id = $1",
+        pending.sub
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    // El login se completó: los fallos anteriores de la cuenta ya no cuentan.
+    if let Err(e) = state.login_guard.clear_account(&state.db_pool, &account).await {
+        tracing::error!("Error al limpiar intentos fallidos: {:?}", e);
+    }
+
+    Ok(Json(tokens::issue_token_pair(&state, pending.sub, role, email_verified).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/setup",
+    responses(
+        (status = 200, description = "Secreto TOTP generado; confirmar con `/api/v1/auth/mfa/enable`", body = MfaSetupResponse),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "MFA ya está activo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para iniciar la configuración de TOTP
+pub async fn setup(
+    State(state): State<AppState>,
+    subject: MfaSubject,
+) -> Result<impl IntoResponse, ApiError> {
+    let email = sqlx::query_scalar!("SELECT email FROM users WHERE id = $1", subject.user_id)
+        .fetch_optional(&state.db_pool)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+
+    let totp = build_totp(Secret::generate(), &state.mfa_policy.issuer, &email).ok_or(ApiError::Internal)?;
+    let secret = totp.secret().to_base32();
+    let provisioning_uri = totp.to_url().map_err(|e| {
+        tracing::error!("Error al generar la URI de TOTP: {:?}", e);
+        ApiError::Internal
+    })?;
+
+    // Un secreto pendiente se puede regenerar; uno ya activo no se toca.
+    let result = sqlx::query!(
+        "INSERT INTO user_mfa (user_id, totp_secret) VALUES ($1, $2)
+         ON CONFLICT (user_id) DO UPDATE SET totp_secret = EXCLUDED.totp_secret, last_used_step = NULL
+         WHERE user_mfa.enabled_at IS NULL",
+        subject.user_id,
+        secret
+    )
+    .execute(&state.db_pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::Conflict("MFA ya está activo; desactívalo antes de configurarlo de nuevo".into()));
+    }
+
+    Ok(Json(MfaSetupResponse { secret, provisioning_uri }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/enable",
+    request_body = MfaCodePayload,
+    responses(
+        (status = 200, description = "MFA activado, devuelve los códigos de recuperación (y tokens si completó un login)", body = MfaEnabledResponse),
+        (status = 400, description = "Código incorrecto o no hay configuración pendiente", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "La cuenta se desactivó después del primer paso del login", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para confirmar el primer código TOTP y activar MFA
+pub async fn enable(
+    State(state): State<AppState>,
+    subject: MfaSubject,
+    Json(payload): Json<MfaCodePayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    let secret = sqlx::query_scalar!(
+        "SELECT totp_secret FROM user_mfa WHERE user_id = $1 AND enabled_at IS NULL FOR UPDATE",
+        subject.user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::BadRequest("No hay una configuración de MFA pendiente; llama antes a /mfa/setup".into()))?;
+
+    if !accept_totp_code(&mut tx, subject.user_id, &secret, &payload.code).await? {
+        return Err(ApiError::BadRequest("El código no es correcto".into()));
+    }
+
+    sqlx::query!(
+        "UPDATE user_mfa SET enabled_at = NOW() WHERE user_id = $1",
+        subject.user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let recovery_codes = replace_recovery_codes(&mut tx, subject.user_id).await?;
+    let token_subject = token_subject(&mut tx, subject.user_id).await?;
+    if subject.pending_login && token_subject.is_none() {
+        // Cuenta desactivada durante el login: no se activa nada ni se emiten tokens
+        return Err(ApiError::Forbidden("La cuenta está desactivada".into()));
+    }
+
+    tx.commit().await?;
+
+    let tokens = match (subject.pending_login, token_subject) {
+        (true, Some((role, email_verified))) => {
+            Some(tokens::issue_token_pair(&state, subject.user_id, role, email_verified).await?)
+        }
+        _ => None,
+    };
+
+    Ok(Json(MfaEnabledResponse { recovery_codes, tokens }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/disable",
+    request_body = MfaCodePayload,
+    responses(
+        (status = 204, description = "MFA desactivado"),
+        (status = 400, description = "Código incorrecto o MFA no está activo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "El rol del usuario exige MFA", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para desactivar MFA
+pub async fn disable(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<MfaCodePayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    if state.mfa_policy.requires(claims.role) {
+        return Err(ApiError::Forbidden("Tu rol exige MFA; no se puede desactivar".into()));
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+    if !accept_second_factor(&mut tx, claims.sub, &payload.code, true).await? {
+        return Err(ApiError::BadRequest("El código no es correcto o MFA no está activo".into()));
+    }
+    sqlx::query!("DELETE FROM user_mfa WHERE user_id = $1", claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!("DELETE FROM mfa_recovery_codes WHERE user_id = $1", claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/mfa/recovery-codes",
+    request_body = MfaCodePayload,
+    responses(
+        (status = 200, description = "Nuevos códigos de recuperación; los anteriores dejan de valer", body = RecoveryCodesResponse),
+        (status = 400, description = "Código TOTP incorrecto o MFA no está activo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para regenerar los códigos de recuperación
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<MfaCodePayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+    // Solo con TOTP: un código de recuperación no debe servir para generar más.
+    if !accept_second_factor(&mut tx, claims.sub, &payload.code, false).await? {
+        return Err(ApiError::BadRequest("El código TOTP no es correcto o MFA no está activo".into()));
+    }
+    let recovery_codes = replace_recovery_codes(&mut tx, claims.sub).await?;
+    tx.commit().await?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery_codes_ignore_case_and_separators() {
+        assert_eq!(normalize_recovery_code("a1b2c-3d4e5"), "a1b2c3d4e5");
+        assert_eq!(normalize_recovery_code("A1B2C-3D4E5"), "a1b2c3d4e5");
+        assert_eq!(normalize_recovery_code("a1b2c3d4e5"), "a1b2c3d4e5");
+        assert_eq!(normalize_recovery_code("  a1b2c 3d4e5\n"), "a1b2c3d4e5");
+        assert_eq!(normalize_recovery_code("a1b2c–3d4e5"), "a1b2c3d4e5");
+    }
+
+    #[test]
+    fn recovery_codes_keep_every_character_that_counts() {
+        assert_ne!(normalize_recovery_code("a1b2c-3d4e5"), normalize_recovery_code("a1b2c-3d4e6"));
+        assert_eq!(normalize_recovery_code("-- --"), "");
+    }
+}
@@ -0,0 +1,157 @@
+// --- Restablecimiento de contraseña ---
+//
+// `forgot` envía por correo un enlace con un token de un solo uso; `reset` lo
+// canjea por una contraseña nueva y cierra todas las sesiones abiertas.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use lms_common::validation::{self, ValidatedJson};
+use lms_common::{ApiError, ProblemDetails};
+
+use lms_common::mail::{self, Email};
+use lms_common::tokens::{generate_token, hash_token};
+use crate::tokens::revoke_all_sessions;
+use crate::{hash_password, AppState};
+
+/// Payload para solicitar el restablecimiento de contraseña.
+#[derive(Deserialize, ToSchema)]
+pub struct ForgotPasswordPayload {
+    #[schema(example = "test@example.com")]
+    email: String,
+}
+
+/// Payload para fijar una contraseña nueva con el token recibido por correo.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct ResetPasswordPayload {
+    token: String,
+    #[schema(example = "NewSecurePassword456", min_length = 8, max_length = 128)]
+    #[validate(length(min = 8, max = 128, message = "Debe tener entre 8 y 128 caracteres"), custom(function = "validation::password_strength"))]
+    new_password: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password/forgot",
+    request_body = ForgotPasswordPayload,
+    responses(
+        (status = 202, description = "Si el email está registrado, se envió un enlace de restablecimiento"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+/// Handler para solicitar un enlace de restablecimiento de contraseña
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = sqlx::query!(
+        "SELECT id, email FROM users WHERE email = $1",
+        payload.email.to_lowercase()
+    )
+    .fetch_optional(&state.db_pool)
+    .await?;
+
+    // La respuesta es la misma exista o no el email, para no revelar qué cuentas existen.
+    let Some(user) = user else {
+        return Ok(StatusCode::ACCEPTED);
+    };
+
+    let token = generate_token();
+    let expires_at = Utc::now() + state.password_reset_ttl;
+
+    // Solo el último enlace solicitado es válido.
+    let mut tx = state.db_pool.begin().await?;
+    sqlx::query!(
+        "UPDATE password_reset_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+        user.id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO password_reset_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+        user.id,
+        hash_token(&token),
+        expires_at
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    mail::send_in_background(
+        state.mailer.clone(),
+        Email {
+            to: user.email,
+            subject: "Restablecer tu contraseña".into(),
+            body: format!(
+                "Recibimos una solicitud para restablecer tu contraseña.\n\n\
+                 Abre este enlace para elegir una nueva (válido por {} minutos):\n{}/reset-password?token={}\n\n\
+                 Si no fuiste tú, ignora este correo.",
+                state.password_reset_ttl.num_minutes(),
+                state.app_base_url,
+                token
+            ),
+        },
+    );
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/password/reset",
+    request_body = ResetPasswordPayload,
+    responses(
+        (status = 204, description = "Contraseña actualizada, todas las sesiones fueron cerradas"),
+        (status = 400, description = "Token inválido, expirado o ya utilizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+/// Handler para fijar una contraseña nueva con un token de restablecimiento
+pub async fn reset_password(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<ResetPasswordPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let password_hash = hash_password(&payload.new_password)?;
+
+    let mut tx = state.db_pool.begin().await?;
+
+    // `FOR UPDATE` evita que el mismo token se canjee dos veces en paralelo.
+    let user_id = sqlx::query_scalar!(
+        "SELECT user_id FROM password_reset_tokens
+         WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+         FOR UPDATE",
+        hash_token(&payload.token)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::BadRequest("El enlace de restablecimiento no es válido, expiró o ya se usó".into()))?;
+
+    sqlx::query!(
+        "UPDATE password_reset_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE users SET password_hash = $2, updated_at = NOW() WHERE id = $1",
+        user_id,
+        password_hash
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    revoke_all_sessions(&mut tx, user_id).await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,364 @@
+// --- Perfil del usuario autenticado (/users/me) ---
+//
+// Cambiar el email no es inmediato: se envía un enlace a la dirección nueva
+// (JWT con audiencia `email-change`) y el cambio se aplica al abrirlo, con la
+// dirección ya verificada. Cambiar el email o la contraseña exige la contraseña
+// actual, y sus fallos cuentan para el bloqueo de login.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use lms_common::validation::{self, ValidatedJson};
+use lms_common::{ApiError, Claims, ProblemDetails, Role};
+
+use crate::lockout::{self, ClientIp};
+use lms_common::mail::{self, Email};
+use crate::tokens::revoke_all_sessions;
+use crate::verification;
+use crate::{hash_password, AppState};
+
+const EMAIL_CHANGE_AUDIENCE: &str = "email-change";
+
+/// Perfil del usuario autenticado.
+#[derive(Serialize, ToSchema)]
+pub struct ProfileResponse {
+    id: Uuid,
+    first_name: String,
+    last_name: String,
+    username: String,
+    email: String,
+    role: Role,
+    email_verified_at: Option<DateTime<Utc>>,
+    mfa_enabled: bool,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+/// Campos del perfil que el usuario puede modificar. Los omitidos no cambian.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct UpdateProfilePayload {
+    #[schema(example = "Juan", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
+    first_name: Option<String>,
+    #[schema(example = "Allende", min_length = 1, max_length = 255)]
+    #[validate(length(min = 1, max = 255, message = "Debe tener entre 1 y 255 caracteres"), custom(function = "validation::not_blank"))]
+    last_name: Option<String>,
+    #[schema(example = "nurfog", min_length = 3, max_length = 50)]
+    #[validate(length(min = 3, max = 50, message = "Debe tener entre 3 y 50 caracteres"), custom(function = "validation::username_chars"))]
+    username: Option<String>,
+}
+
+/// Payload para solicitar el cambio de email.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct ChangeEmailPayload {
+    #[schema(example = "nuevo@example.com", max_length = 255)]
+    #[validate(email(message = "No es un email válido"), length(max = 255, message = "Debe tener como máximo 255 caracteres"))]
+    new_email: String,
+    #[schema(example = "SecurePassword123")]
+    current_password: String,
+}
+
+/// Payload para confirmar el cambio de email con el token recibido.
+#[derive(Deserialize, ToSchema)]
+pub struct ConfirmEmailChangePayload {
+    token: String,
+}
+
+/// Payload para cambiar la contraseña.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct ChangePasswordPayload {
+    #[schema(example = "SecurePassword123")]
+    current_password: String,
+    #[schema(example = "NewSecurePassword456", min_length = 8, max_length = 128)]
+    #[validate(length(min = 8, max = 128, message = "Debe tener entre 8 y 128 caracteres"), custom(function = "validation::password_strength"))]
+    new_password: String,
+}
+
+/// Claims del token incluido en el enlace de cambio de email. Lleva también el
+/// email anterior para que el enlace deje de valer si el email cambia por otra vía.
+#[derive(Serialize, Deserialize)]
+struct EmailChangeClaims {
+    sub: Uuid,
+    old_email: String,
+    new_email: String,
+    aud: String,
+    exp: i64,
+}
+
+async fn fetch_profile(state: &AppState, id: Uuid) -> Result<ProfileResponse, ApiError> {
+    sqlx::query_as!(
+        ProfileResponse,
+        "SELECT u.id, u.first_name, u.last_name, u.username, u.email, u.role as \"role: _\",
+                u.email_verified_at, u.created_at, u.updated_at,
+                EXISTS (SELECT 1 FROM user_mfa m WHERE m.user_id = u.id AND m.enabled_at IS NOT NULL) AS \"mfa_enabled!\"
+         FROM users u WHERE u.id = $1",
+        id
+    )
+    .fetch_optional(&state.db_pool)
+    .await?
+    .ok_or(ApiError::Unauthorized) // La cuenta se eliminó después de emitir el token
+}
+
+/// Comprueba la contraseña actual del usuario aplicando el bloqueo de login.
+/// Devuelve su email.
+async fn check_current_password(
+    state: &AppState,
+    user_id: Uuid,
+    ip: &str,
+    password: &str,
+) -> Result<String, ApiError> {
+    let user = sqlx::query!("SELECT email, password_hash FROM users WHERE id = $1", user_id)
+        .fetch_optional(&state.db_pool)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+
+    if let Some(wait) = state.login_guard.retry_after(&state.db_pool, Some(&user.email), ip).await? {
+        return Err(lockout::too_many_attempts(wait));
+    }
+
+    if !argon2::verify_encoded(&user.password_hash, password.as_bytes()).unwrap_or(false) {
+        if let Err(e) = state.login_guard.record_failure(&state.db_pool, Some(&user.email), ip).await {
+            tracing::error!("Error al registrar intento fallido: {:?}", e);
+        }
+        return Err(ApiError::Forbidden("La contraseña actual es incorrecta".into()));
+    }
+
+    Ok(user.email)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/me",
+    responses(
+        (status = 200, description = "Perfil del usuario autenticado", body = ProfileResponse),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para obtener el perfil propio
+pub async fn get_me(State(state): State<AppState>, claims: Claims) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(fetch_profile(&state, claims.sub).await?))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/users/me",
+    request_body = UpdateProfilePayload,
+    responses(
+        (status = 200, description = "Perfil actualizado", body = ProfileResponse),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El username ya está en uso", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para actualizar nombre, apellido y username propios
+pub async fn update_me(
+    State(state): State<AppState>,
+    claims: Claims,
+    ValidatedJson(payload): ValidatedJson<UpdateProfilePayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    // Actualizar solo los campos proporcionados (409 si el username ya está en uso)
+    sqlx::query!(
+        "UPDATE users SET
+            first_name = COALESCE($2, first_name),
+            last_name = COALESCE($3, last_name),
+            username = COALESCE($4, username),
+            updated_at = NOW()
+         WHERE id = $1",
+        claims.sub,
+        payload.first_name,
+        payload.last_name,
+        payload.username
+    )
+    .execute(&state.db_pool)
+    .await?;
+
+    Ok(Json(fetch_profile(&state, claims.sub).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/me/email",
+    request_body = ChangeEmailPayload,
+    responses(
+        (status = 202, description = "Se envió un enlace de confirmación a la dirección nueva; el email no cambia hasta abrirlo"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "La contraseña actual es incorrecta", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El email ya está en uso", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Demasiados intentos fallidos", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para solicitar el cambio del email propio
+pub async fn request_email_change(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    ValidatedJson(payload): ValidatedJson<ChangeEmailPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let old_email = check_current_password(&state, claims.sub, &ip, &payload.current_password).await?;
+
+    let new_email = payload.new_email.trim().to_lowercase();
+
+    let taken = sqlx::query_scalar!("SELECT EXISTS (SELECT 1 FROM users WHERE email = $1) AS \"exists!\"", new_email)
+        .fetch_one(&state.db_pool)
+        .await?;
+    if taken {
+        return Err(ApiError::Conflict("El email ya está en uso".into()));
+    }
+
+    let change = EmailChangeClaims {
+        sub: claims.sub,
+        old_email,
+        new_email: new_email.clone(),
+        aud: EMAIL_CHANGE_AUDIENCE.to_string(),
+        exp: (Utc::now() + state.email_verification_ttl).timestamp(),
+    };
+    let token = state.keys.sign(&change).map_err(|e| {
+        tracing::error!("Error al firmar token de cambio de email: {:?}", e);
+        ApiError::Internal
+    })?;
+
+    mail::send_in_background(
+        state.mailer.clone(),
+        Email {
+            to: new_email,
+            subject: "Confirma tu nueva dirección de email".into(),
+            body: format!(
+                "Abre este enlace para usar esta dirección en tu cuenta (válido por {} horas):\n{}/confirm-email-change?token={}\n\n\
+                 Si no lo solicitaste, ignora este correo.",
+                state.email_verification_ttl.num_hours(),
+                state.app_base_url,
+                token
+            ),
+        },
+    );
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/email/change/confirm",
+    request_body = ConfirmEmailChangePayload,
+    responses(
+        (status = 204, description = "Email cambiado y marcado como verificado"),
+        (status = 400, description = "Token inválido, expirado o el email de la cuenta cambió desde entonces", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "El email nuevo ya está en uso", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+/// Handler para confirmar el cambio de email con el enlace recibido
+pub async fn confirm_email_change(
+    State(state): State<AppState>,
+    Json(payload): Json<ConfirmEmailChangePayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let change = state
+        .keys
+        .verify::<EmailChangeClaims>(&payload.token, Some(EMAIL_CHANGE_AUDIENCE))
+        .ok_or_else(|| ApiError::BadRequest("El enlace de cambio de email no es válido o expiró".into()))?;
+
+    // 409 si el nuevo email ya está en uso
+    let result = sqlx::query!(
+        "UPDATE users SET email = $3, email_verified_at = NOW(), updated_at = NOW()
+         WHERE id = $1 AND email = $2",
+        change.sub,
+        change.old_email,
+        change.new_email
+    )
+    .execute(&state.db_pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::BadRequest(
+            "El email de la cuenta cambió después de solicitar este enlace".into(),
+        ));
+    }
+
+    // La nueva dirección ya está verificada: sus invitaciones pendientes se reclaman
+    verification::claim_course_invitations(&state, change.sub, &change.new_email).await;
+
+    // Aviso a la dirección anterior, por si el cambio no fue del titular.
+    mail::send_in_background(
+        state.mailer.clone(),
+        Email {
+            to: change.old_email,
+            subject: "Tu dirección de email cambió".into(),
+            body: format!(
+                "El email de tu cuenta se cambió a {}.\n\nSi no fuiste tú, contacta al soporte.",
+                change.new_email
+            ),
+        },
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/me/password",
+    request_body = ChangePasswordPayload,
+    responses(
+        (status = 204, description = "Contraseña cambiada; todas las sesiones se cerraron y hay que volver a iniciar sesión"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "La contraseña actual es incorrecta", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Datos inválidos, con el detalle por campo", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Demasiados intentos fallidos", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para cambiar la contraseña propia
+pub async fn change_password(
+    State(state): State<AppState>,
+    claims: Claims,
+    ClientIp(ip): ClientIp,
+    ValidatedJson(payload): ValidatedJson<ChangePasswordPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let email = check_current_password(&state, claims.sub, &ip, &payload.current_password).await?;
+    let password_hash = hash_password(&payload.new_password)?;
+
+    let mut tx = state.db_pool.begin().await?;
+    sqlx::query!(
+        "UPDATE users SET password_hash = $2, updated_at = NOW() WHERE id = $1",
+        claims.sub,
+        password_hash
+    )
+    .execute(&mut *tx)
+    .await?;
+    revoke_all_sessions(&mut tx, claims.sub).await?;
+    tx.commit().await?;
+
+    mail::send_in_background(
+        state.mailer.clone(),
+        Email {
+            to: email,
+            subject: "Tu contraseña cambió".into(),
+            body: "La contraseña de tu cuenta se cambió y se cerraron todas las sesiones.\n\n\
+                   Si no fuiste tú, restablécela de inmediato y contacta al soporte."
+                .into(),
+        },
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,320 @@
+// --- Emisión y rotación de tokens ---
+//
+// Cada login crea una "familia" de refresh tokens. Al refrescar, el token usado
+// se revoca y se reemplaza por uno nuevo de la misma familia. Si alguien presenta
+// un token ya revocado (p. ej. robado y usado después que el legítimo), se revoca
+// la familia completa y el usuario debe volver a autenticarse.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::{PgConnection, PgPool};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use lms_common::auth::roles::Admin;
+use lms_common::tokens::{generate_token, hash_token};
+use lms_common::{ApiError, Claims, ProblemDetails, RequireRole, Role};
+
+use crate::{AppState, TokenResponse};
+
+/// Payload para el endpoint de refresh.
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshPayload {
+    #[schema(example = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08")]
+    refresh_token: String,
+}
+
+/// Payload opcional para el endpoint de logout.
+#[derive(Deserialize, ToSchema)]
+pub struct LogoutPayload {
+    /// Si se envía, también se revoca la familia de este refresh token.
+    refresh_token: Option<String>,
+}
+
+/// Firma un access token JWT de corta duración.
+fn issue_access_token(
+    state: &AppState,
+    user_id: Uuid,
+    role: Role,
+    email_verified: bool,
+) -> Result<String, ApiError> {
+    let now = Utc::now();
+    let claims = Claims {
+        sub: user_id,
+        role,
+        exp: (now + state.access_token_ttl).timestamp(),
+        iat: now.timestamp(),
+        jti: Uuid::new_v4(),
+        email_verified,
+        iat_ms: Some(now.timestamp_millis()),
+    };
+
+    state.keys.sign(&claims).map_err(|e| {
+        tracing::error!("Error al firmar el access token: {:?}", e);
+        ApiError::Internal
+    })
+}
+
+/// Persiste un nuevo refresh token dentro de `family_id` y devuelve su ID junto al token en claro.
+async fn insert_refresh_token(
+    conn: &mut PgConnection,
+    state: &AppState,
+    user_id: Uuid,
+    family_id: Uuid,
+) -> Result<(Uuid, String), sqlx::Error> {
+    let token = generate_token();
+    let expires_at = Utc::now() + state.refresh_token_ttl;
+
+    let id = sqlx::query_scalar!(
+        "INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at) VALUES ($1, $2, $3, $4) RETURNING id",
+        user_id,
+        family_id,
+        hash_token(&token),
+        expires_at
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok((id, token))
+}
+
+/// Emite un par access/refresh token para un login nuevo (nueva familia de tokens).
+pub async fn issue_token_pair(
+    state: &AppState,
+    user_id: Uuid,
+    role: Role,
+    email_verified: bool,
+) -> Result<TokenResponse, ApiError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let (_, refresh_token) = insert_refresh_token(&mut conn, state, user_id, Uuid::new_v4()).await?;
+    let token = issue_access_token(state, user_id, role, email_verified)?;
+
+    Ok(TokenResponse::new(token, refresh_token, state))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh",
+    request_body = RefreshPayload,
+    responses(
+        (status = 200, description = "Tokens rotados, devuelve un nuevo par access/refresh", body = TokenResponse),
+        (status = 401, description = "Refresh token inválido, expirado o reutilizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+/// Handler para rotar un refresh token y obtener un nuevo access token
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    rotate_refresh_token(&state, &payload.refresh_token)
+        .await?
+        .map(Json)
+        .ok_or_else(|| {
+            ApiError::Status(
+                StatusCode::UNAUTHORIZED,
+                Some("El refresh token no es válido, expiró o ya se usó; inicia sesión de nuevo".into()),
+            )
+        })
+}
+
+/// Rota un refresh token dentro de una transacción. Devuelve `None` si el token
+/// no es aceptable (desconocido, expirado o reutilizado).
+async fn rotate_refresh_token(
+    state: &AppState,
+    presented: &str,
+) -> Result<Option<TokenResponse>, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+
+    // `FOR UPDATE` serializa dos refresh concurrentes con el mismo token:
+    // el segundo verá el token ya revocado y se tratará como reutilización.
+    let stored = sqlx::query!(
+        "SELECT rt.id, rt.user_id, rt.family_id, rt.expires_at, rt.revoked_at, u.role as \"role: Role\", u.email_verified_at
+         FROM refresh_tokens rt
+         JOIN users u ON u.id = rt.user_id
+         WHERE rt.token_hash = $1
+         FOR UPDATE OF rt",
+        hash_token(presented)
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(stored) = stored else {
+        return Ok(None);
+    };
+
+    if stored.revoked_at.is_some() {
+        // Reutilización detectada: se invalida toda la familia.
+        sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE family_id = $1 AND revoked_at IS NULL",
+            stored.family_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        tracing::warn!(
+            user_id = %stored.user_id,
+            family_id = %stored.family_id,
+            "Reutilización de refresh token detectada, familia revocada"
+        );
+        return Ok(None);
+    }
+
+    if stored.expires_at <= Utc::now() {
+        return Ok(None);
+    }
+
+    let (new_id, refresh_token) =
+        insert_refresh_token(&mut tx, state, stored.user_id, stored.family_id).await?;
+
+    sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = NOW(), replaced_by = $2 WHERE id = $1",
+        stored.id,
+        new_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let token = issue_access_token(
+        state,
+        stored.user_id,
+        stored.role,
+        stored.email_verified_at.is_some(),
+    )?;
+
+    Ok(Some(TokenResponse::new(token, refresh_token, state)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    request_body(content = Option<LogoutPayload>, description = "Refresh token de la sesión a cerrar (opcional)"),
+    responses(
+        (status = 204, description = "Sesión cerrada, el access token queda revocado"),
+        (status = 401, description = "No autorizado (token inválido, ausente o ya revocado)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para cerrar la sesión actual
+pub async fn logout(
+    State(state): State<AppState>,
+    claims: Claims,
+    payload: Option<Json<LogoutPayload>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let expires_at = chrono::DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now);
+
+    sqlx::query!(
+        "INSERT INTO revoked_tokens (jti, user_id, expires_at) VALUES ($1, $2, $3) ON CONFLICT (jti) DO NOTHING",
+        claims.jti,
+        claims.sub,
+        expires_at
+    )
+    .execute(&state.db_pool)
+    .await?;
+
+    if let Some(refresh_token) = payload.and_then(|Json(p)| p.refresh_token) {
+        // Solo se revoca la familia si el refresh token pertenece al mismo usuario.
+        sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = NOW()
+             WHERE revoked_at IS NULL
+               AND family_id = (SELECT family_id FROM refresh_tokens WHERE token_hash = $1 AND user_id = $2)",
+            hash_token(&refresh_token),
+            claims.sub
+        )
+        .execute(&state.db_pool)
+        .await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{id}/sessions/revoke",
+    params(
+        ("id" = Uuid, Path, description = "ID del usuario")
+    ),
+    responses(
+        (status = 204, description = "Todas las sesiones del usuario fueron revocadas"),
+        (status = 401, description = "No autorizado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Prohibido (el usuario no es administrador)", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Usuario no encontrado", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+/// Handler para revocar todas las sesiones de un usuario (solo administradores)
+pub async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    _admin: RequireRole<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.db_pool.begin().await?;
+    if !revoke_all_sessions(&mut tx, id).await? {
+        return Err(ApiError::NotFound("El usuario no existe".into()));
+    }
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Invalida todos los access tokens emitidos hasta ahora y todos los refresh
+/// tokens vigentes del usuario. Devuelve `false` si el usuario no existe.
+pub async fn revoke_all_sessions(conn: &mut PgConnection, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    // `clock_timestamp()` y no `NOW()` (inicio de la transacción): también cubre los
+    // tokens emitidos mientras la transacción estaba abierta.
+    let updated = sqlx::query!(
+        "UPDATE users SET sessions_revoked_at = clock_timestamp() WHERE id = $1",
+        user_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+        user_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(true)
+}
+
+/// Borra periódicamente las revocaciones de tokens que ya habrían expirado: la
+/// firma los rechaza por `exp`, así que la fila ya no hace falta.
+pub fn spawn_revocation_cleanup_task(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            match sqlx::query!("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+                .execute(&pool)
+                .await
+            {
+                Ok(r) if r.rows_affected() > 0 => {
+                    tracing::info!(purged = r.rows_affected(), "Revocaciones de tokens expirados purgadas")
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Error al purgar revocaciones de tokens: {:?}", e),
+            }
+        }
+    });
+}
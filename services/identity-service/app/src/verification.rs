@@ -0,0 +1,188 @@
+// --- Verificación de email ---
+//
+// Al registrarse, el usuario recibe un enlace con un JWT firmado (audiencia
+// `email-verification`) que incluye su email: si la dirección cambia, los
+// enlaces anteriores dejan de servir.
+//
+// `EMAIL_VERIFICATION_POLICY` decide qué se exige a los usuarios sin verificar:
+// - `none`: nada.
+// - `enrollment` (por defecto): pueden iniciar sesión; el access token lleva
+//   `email_verified = false` para que course-service rechace la inscripción.
+// - `login`: el login se rechaza hasta verificar el email.
+//
+// Al verificar un email (también al confirmar un cambio de email) se reclaman las
+// invitaciones a cursos que course-service dejó pendientes para esa dirección.
+
+use std::env;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use lms_common::{ApiError, ProblemDetails};
+
+use lms_common::mail::{self, Email};
+use crate::AppState;
+
+const AUDIENCE: &str = "email-verification";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmailVerificationPolicy {
+    None,
+    Enrollment,
+    Login,
+}
+
+impl EmailVerificationPolicy {
+    pub fn from_env() -> Self {
+        match env::var("EMAIL_VERIFICATION_POLICY").as_deref() {
+            Ok("none") => Self::None,
+            Ok("enrollment") | Err(_) => Self::Enrollment,
+            Ok("login") => Self::Login,
+            Ok(other) => panic!(
+                "EMAIL_VERIFICATION_POLICY desconocida: {other} (usar `none`, `enrollment` o `login`)"
+            ),
+        }
+    }
+}
+
+/// Claims del token incluido en el enlace de verificación.
+#[derive(Serialize, Deserialize)]
+struct VerificationClaims {
+    sub: Uuid,
+    email: String,
+    aud: String,
+    exp: i64,
+}
+
+/// Payload para confirmar un email con el token recibido.
+#[derive(Deserialize, ToSchema)]
+pub struct VerifyEmailPayload {
+    token: String,
+}
+
+/// Payload para reenviar el enlace de verificación.
+#[derive(Deserialize, ToSchema)]
+pub struct ResendVerificationPayload {
+    #[schema(example = "test@example.com")]
+    email: String,
+}
+
+/// Envía (en segundo plano) el enlace de verificación para `email`.
+pub fn send_verification_email(state: &AppState, user_id: Uuid, email: &str) {
+    let claims = VerificationClaims {
+        sub: user_id,
+        email: email.to_string(),
+        aud: AUDIENCE.to_string(),
+        exp: (Utc::now() + state.email_verification_ttl).timestamp(),
+    };
+
+    let token = match state.keys.sign(&claims) {
+        Ok(t) => t,
+        Err(e) => {
+            tracing::error!("Error al firmar token de verificación: {:?}", e);
+            return;
+        }
+    };
+
+    mail::send_in_background(
+        state.mailer.clone(),
+        Email {
+            to: email.to_string(),
+            subject: "Confirma tu dirección de email".into(),
+            body: format!(
+                "Confirma tu dirección de email abriendo este enlace (válido por {} horas):\n{}/verify-email?token={}",
+                state.email_verification_ttl.num_hours(),
+                state.app_base_url,
+                token
+            ),
+        },
+    );
+}
+
+/// Inscribe al usuario en los cursos a los que invitaron a `email` antes de que
+/// tuviera cuenta (ver `claim_course_invitations` en las migraciones). La función
+/// solo actúa si el email está verificado y el usuario es estudiante. Un fallo se
+/// registra pero no interrumpe la petición: la invitación sigue pendiente.
+pub async fn claim_course_invitations(state: &AppState, user_id: Uuid, email: &str) {
+    match sqlx::query_scalar!("SELECT claim_course_invitations($1, $2) AS \"claimed!\"", user_id, email)
+        .fetch_one(&state.db_pool)
+        .await
+    {
+        Ok(0) => {}
+        Ok(claimed) => tracing::info!(user_id = %user_id, claimed, "Invitaciones a cursos reclamadas"),
+        Err(e) => tracing::error!("No se pudieron reclamar las invitaciones a cursos: {:?}", e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/email/verify",
+    request_body = VerifyEmailPayload,
+    responses(
+        (status = 204, description = "Email verificado. Los access tokens emitidos a partir de ahora lo reflejan, y un estudiante queda inscrito en los cursos a los que lo invitaron con ese email"),
+        (status = 400, description = "Token inválido, expirado o de un email que ya no es el del usuario", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+/// Handler para confirmar la dirección de email
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let claims = state
+        .keys
+        .verify::<VerificationClaims>(&payload.token, Some(AUDIENCE))
+        .ok_or_else(|| ApiError::BadRequest("El enlace de verificación no es válido o expiró".into()))?;
+
+    let result = sqlx::query!(
+        "UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()), updated_at = NOW() WHERE id = $1 AND email = $2",
+        claims.sub,
+        claims.email
+    )
+    .execute(&state.db_pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::BadRequest(
+            "El enlace de verificación es de una dirección que ya no es la de la cuenta".into(),
+        ));
+    }
+
+    claim_course_invitations(&state, claims.sub, &claims.email).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/email/resend",
+    request_body = ResendVerificationPayload,
+    responses(
+        (status = 202, description = "Si el email está registrado y sin verificar, se envió un nuevo enlace"),
+        (status = 500, description = "Error interno del servidor", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+/// Handler para reenviar el enlace de verificación de email
+pub async fn resend_verification(
+    State(state): State<AppState>,
+    Json(payload): Json<ResendVerificationPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = sqlx::query!(
+        "SELECT id, email FROM users WHERE email = $1 AND email_verified_at IS NULL",
+        payload.email.to_lowercase()
+    )
+    .fetch_optional(&state.db_pool)
+    .await?;
+
+    // La respuesta es la misma exista o no el email, para no revelar qué cuentas existen.
+    if let Some(user) = user {
+        send_verification_email(&state, user.id, &user.email);
+    }
+    Ok(StatusCode::ACCEPTED)
+}
@@ -1,10 +1,8 @@
 use axum::{extract::State, routing::{get, get_service}, Json, Router};
-use std::env;
 use std::net::SocketAddr;
 use tower_http::services::ServeDir;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use lms_common::config;
 use serde::{Deserialize, Serialize};
-use reqwest;
 
 // --- Estado de la Aplicación ---
 #[derive(Clone)]
@@ -24,18 +22,11 @@ struct EndpointInfo {
 
 #[tokio::main]
 async fn main() {
-    // Inicializar el sistema de logging (tracing)
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-    
-    let identity_service_url = env::var("IDENTITY_SERVICE_URL")
-        .expect("IDENTITY_SERVICE_URL must be set");
-    let course_service_url = env::var("COURSE_SERVICE_URL")
-        .expect("COURSE_SERVICE_URL must be set");
+    // Inicializar el sistema de logging (tracing) y la configuración
+    config::init("portal-service");
+
+    let identity_service_url = config::required("IDENTITY_SERVICE_URL");
+    let course_service_url = config::required("COURSE_SERVICE_URL");
 
     let app_state = AppState {
         identity_service_url,
@@ -62,48 +53,44 @@ async fn get_all_endpoints(State(state): State<AppState>) -> Json<Vec<EndpointIn
     let mut all_endpoints: Vec<EndpointInfo> = Vec::new();
 
     // Fetch Identity Service OpenAPI spec
-    if let Ok(response) = client.get(&format!("{}/api-docs/openapi.json", state.identity_service_url))
+    if let Ok(response) = client.get(format!("{}/api-docs/openapi.json", state.identity_service_url))
         .send()
         .await
+        && let Ok(openapi_json) = response.json::<serde_json::Value>().await
+        && let Some(paths) = openapi_json["paths"].as_object()
     {
-        if let Ok(openapi_json) = response.json::<serde_json::Value>().await {
-            if let Some(paths) = openapi_json["paths"].as_object() {
-                for (path, methods) in paths {
-                    if let Some(methods_obj) = methods.as_object() {
-                        for (method, details) in methods_obj {
-                            all_endpoints.push(EndpointInfo {
-                                service: "Identity".to_string(),
-                                path: path.clone(),
-                                method: method.to_uppercase(),
-                                summary: details["summary"].as_str().map(|s| s.to_string()),
-                            });
-                        }
-                    }
+        for (path, methods) in paths {
+            if let Some(methods_obj) = methods.as_object() {
+                for (method, details) in methods_obj {
+                    all_endpoints.push(EndpointInfo {
+                        service: "Identity".to_string(),
+                        path: path.clone(),
+                        method: method.to_uppercase(),
+                        summary: details["summary"].as_str().map(|s| s.to_string()),
+                    });
                 }
             }
         }
     }
 
     // Fetch Course Service OpenAPI spec
-    if let Ok(response) = client.get(&format!("{}/api-docs/openapi.json", state.course_service_url))
+    if let Ok(response) = client.get(format!("{}/api-docs/openapi.json", state.course_service_url))
         .send()
         .await
+        && let Ok(openapi_json) = response.json::<serde_json::Value>().await
+        && let Some(paths) = openapi_json["paths"].as_object()
     {
-        if let Ok(openapi_json) = response.json::<serde_json::Value>().await {
-            // Note: Course Service currently only has health_check, so this will be minimal.
-            // We'll expand this when course service gets more endpoints.
-            if let Some(paths) = openapi_json["paths"].as_object() {
-                for (path, methods) in paths {
-                    if let Some(methods_obj) = methods.as_object() {
-                        for (method, details) in methods_obj {
-                            all_endpoints.push(EndpointInfo {
-                                service: "Course".to_string(),
-                                path: path.clone(),
-                                method: method.to_uppercase(),
-                                summary: details["summary"].as_str().map(|s| s.to_string()),
-                            });
-                        }
-                    }
+        // Note: Course Service currently only has health_check, so this will be minimal.
+        // We'll expand this when course service gets more endpoints.
+        for (path, methods) in paths {
+            if let Some(methods_obj) = methods.as_object() {
+                for (method, details) in methods_obj {
+                    all_endpoints.push(EndpointInfo {
+                        service: "Course".to_string(),
+                        path: path.clone(),
+                        method: method.to_uppercase(),
+                        summary: details["summary"].as_str().map(|s| s.to_string()),
+                    });
                 }
             }
         }